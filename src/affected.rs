@@ -0,0 +1,227 @@
+use crate::types::errors::Error;
+use crate::types::models::IsotarpAnalysis;
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::process::Command;
+
+/// Lines of a file touched by a diff, numbered as in the base revision
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct FileChange {
+    pub path: String,
+    /// Changed or removed lines, plus the lines either side of pure insertions
+    pub lines: BTreeSet<u64>,
+}
+
+/// Tests whose coverage intersects a set of changes
+#[derive(Debug, Default)]
+pub struct AffectedTests {
+    pub tests: BTreeSet<String>,
+    /// Changed files that do not appear in the analysis (new files, non-source files, ...)
+    pub unmapped_files: Vec<String>,
+    /// Changed files matched by function spans because the report has no line index
+    pub function_level_files: Vec<String>,
+    /// Changed files without a line index with lines outside every known function (struct
+    /// fields, consts, macros, or a file that failed to parse), for which every test covering
+    /// the file was selected
+    pub file_level_files: Vec<String>,
+}
+
+// Strip the "a/" or "b/" prefix (and any quoting) git puts on diff header paths
+fn header_path(path: &str) -> Option<String> {
+    let path = path.trim().trim_matches('"');
+    if path == "/dev/null" {
+        return None;
+    }
+    let path = path
+        .strip_prefix("a/")
+        .or_else(|| path.strip_prefix("b/"))
+        .unwrap_or(path);
+    Some(path.to_string())
+}
+
+// Parse the old-side start line and line count from a hunk header like "@@ -12,3 +12,4 @@"
+fn hunk_old_range(header: &str) -> Option<(u64, u64)> {
+    let old = header.strip_prefix("@@ -")?.split_whitespace().next()?;
+    let mut parts = old.splitn(2, ',');
+    let start = parts.next()?.parse().ok()?;
+    let count = match parts.next() {
+        Some(count) => count.parse().ok()?,
+        None => 1,
+    };
+    Some((start, count))
+}
+
+/// Parse zero-context unified diff output into the base-revision lines changed in each file
+pub fn parse_diff(diff: &str) -> Vec<FileChange> {
+    let mut changes: Vec<FileChange> = Vec::new();
+    let mut old_path: Option<String> = None;
+    // File headers only appear between "diff --git" and the first hunk, so removed or added
+    // lines that happen to start with "--- " or "+++ " are not mistaken for them
+    let mut in_header = false;
+    let mut is_new_file = false;
+
+    for line in diff.lines() {
+        if line.starts_with("diff --git ") {
+            in_header = true;
+        } else if let (true, Some(path)) = (in_header, line.strip_prefix("--- ")) {
+            old_path = header_path(path);
+        } else if let (true, Some(path)) = (in_header, line.strip_prefix("+++ ")) {
+            // New files have no base lines, but are still recorded as changed
+            is_new_file = old_path.is_none();
+            let path = old_path.take().or_else(|| header_path(path));
+            if let Some(path) = path {
+                changes.push(FileChange {
+                    path,
+                    lines: BTreeSet::new(),
+                });
+            }
+        } else if line.starts_with("@@ ") {
+            in_header = false;
+            let (Some(change), Some((start, count))) = (changes.last_mut(), hunk_old_range(line))
+            else {
+                continue;
+            };
+            if is_new_file {
+                continue;
+            }
+            if count > 0 {
+                change.lines.extend(start..start + count);
+            } else {
+                // A pure insertion after line `start` touches the lines either side of it
+                change
+                    .lines
+                    .extend([start, start + 1].into_iter().filter(|l| *l > 0));
+            }
+        }
+    }
+
+    changes
+}
+
+/// Run `git diff` against a revision in the current repository, without context lines
+pub fn git_diff(revision: &str) -> Result<String, Error> {
+    let output = Command::new("git")
+        .args(["diff", "-U0", "--no-color", "--no-ext-diff", revision])
+        .output()
+        .map_err(|e| Error::CommandFailed(format!("Failed to execute 'git diff': {}", e)))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::CommandFailed(format!(
+            "git diff {} failed: {}\nStderr: {}",
+            revision, output.status, stderr
+        )));
+    }
+
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+/// Get the top-level directory of the current git repository, which diff paths are relative to
+pub fn git_toplevel() -> Result<String, Error> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--show-toplevel"])
+        .output()
+        .map_err(|e| Error::CommandFailed(format!("Failed to execute 'git rev-parse': {}", e)))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::CommandFailed(format!(
+            "git rev-parse --show-toplevel failed: {}\nStderr: {}",
+            output.status, stderr
+        )));
+    }
+
+    Ok(String::from_utf8(output.stdout)?.trim_end().to_string())
+}
+
+/// Find the file a diff path refers to in the analysis
+///
+/// Diff paths are relative to the repository's top level and report files to the workspace
+/// root, so each is resolved to the same absolute path before they are compared. Files of
+/// crates outside the workspace, and of reports written before paths were relative, are
+/// named by absolute path.
+fn report_file<'a>(
+    analysis: &'a IsotarpAnalysis,
+    path: &str,
+    repo_root: &Path,
+    workspace_root: &Path,
+) -> Option<&'a str> {
+    let absolute = repo_root.join(path);
+    let relative = absolute.strip_prefix(workspace_root).ok().map(|relative| {
+        relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy().to_string())
+            .collect::<Vec<_>>()
+            .join("/")
+    });
+
+    relative
+        .into_iter()
+        .chain([absolute.to_string_lossy().to_string()])
+        .find_map(|file| analysis.files.get_key_value(&file))
+        .map(|(file, _)| file.as_str())
+}
+
+/// Select the tests covering any line touched by the changes
+///
+/// Lines are matched exactly when the report has a line index. Otherwise a changed line
+/// selects every test reaching the function that encloses it.
+pub fn find_affected_tests(
+    analysis: &IsotarpAnalysis,
+    changes: &[FileChange],
+    repo_root: &Path,
+    workspace_root: &Path,
+) -> AffectedTests {
+    let mut affected = AffectedTests::default();
+
+    for change in changes {
+        let Some(file) = report_file(analysis, &change.path, repo_root, workspace_root) else {
+            affected.unmapped_files.push(change.path.clone());
+            continue;
+        };
+
+        let summary = &analysis.files[file];
+
+        if let Some(lines) = &summary.lines {
+            for line in &change.lines {
+                if let Some(tests) = lines.get(line) {
+                    affected.tests.extend(tests.iter().cloned());
+                }
+            }
+            continue;
+        }
+
+        for function in summary.functions.values() {
+            let touched = change
+                .lines
+                .range(function.start_line..=function.end_line)
+                .next()
+                .is_some();
+            if touched {
+                affected.tests.extend(function.tests.iter().cloned());
+            }
+        }
+        affected.function_level_files.push(change.path.clone());
+
+        // A change outside any function may affect any test reaching the file, so select them
+        // all rather than silently too few
+        let outside_functions = change.lines.iter().any(|line| {
+            !summary
+                .functions
+                .values()
+                .any(|function| (function.start_line..=function.end_line).contains(line))
+        });
+        if outside_functions {
+            affected.tests.extend(
+                analysis
+                    .tests
+                    .iter()
+                    .filter(|(_, test_analysis)| test_analysis.files.contains_key(file))
+                    .map(|(test_name, _)| test_name.clone()),
+            );
+            affected.file_level_files.push(change.path.clone());
+        }
+    }
+
+    affected
+}
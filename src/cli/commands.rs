@@ -1,10 +1,28 @@
-use crate::coverage::analysis::run_analysis;
+use crate::affected::{find_affected_tests, git_diff, git_toplevel, parse_diff};
+use crate::coverage::analysis::{
+    build_analysis, collect_from_reports, collect_test_coverage, packages_label,
+};
+use crate::coverage::merge::merge_partials;
+use crate::coverage::minimize::minimize_test_suite;
+use crate::coverage::similarity::compute_test_similarity;
 use crate::coverage::tarpaulin::list_tests;
-use crate::resolve::resolve_test_patterns;
-use crate::types::models::TargetMode;
+use crate::diff::diff_analyses;
+use crate::export::{
+    render_cobertura, render_coverage_table, render_html, render_lcov, render_line_table,
+    render_markdown,
+};
+use crate::resolve::{find_prefix_collisions, resolve_test_patterns, shard_tests};
+use crate::types::errors::Error;
+use crate::types::models::{
+    CollectOptions, CollectedCoverage, IsotarpAnalysis, PartialCoverage, SCHEMA_VERSION, Shard,
+    SimilarityMetric, TargetMode,
+};
 use crate::utils::cleanup::cleanup_target_dirs;
-use crate::utils::io::save_analysis;
-use clap::{Parser, Subcommand};
+use crate::utils::io::{load_analysis, load_partial, save_analysis, save_partial};
+use crate::utils::metadata::{package_roots, workspace_members, workspace_root};
+use crate::utils::paths::line_table_path;
+use crate::utils::ranges::format_line_ranges;
+use clap::{Args, Parser, Subcommand};
 use std::path::{Path, PathBuf};
 
 #[derive(Parser)]
@@ -23,16 +41,18 @@ pub struct Cli {
 pub enum Commands {
     /// List all tests in a package
     List {
-        /// Package name
-        #[arg(short, long)]
-        package: String,
+        #[command(flatten)]
+        packages: PackageArgs,
+
+        /// Also list the package's doctests
+        #[arg(long)]
+        doctests: bool,
     },
 
     /// Run analysis on all tests or specific tests
     Analyze {
-        /// Package name
-        #[arg(short, long)]
-        package: String,
+        #[command(flatten)]
+        packages: PackageArgs,
 
         /// Specific tests to analyze (if not provided, all tests will be analyzed)
         #[arg(short, long)]
@@ -46,35 +66,181 @@ pub enum Commands {
         #[arg(short, long, default_value = "isotarp-analysis.json")]
         report: PathBuf,
 
-        /// Target directory mode: "per" creates a separate target dir for each test (default),
-        /// "one" reuses a single target dir sequentially (saves disk space but may be slower)
-        #[arg(short = 'm', long, default_value_t = TargetMode::default(), value_name="MODE")]
-        target_mode: TargetMode,
+        #[command(flatten)]
+        collect: CollectArgs,
+
+        /// Include, per file, every covered line and the tests that cover it
+        #[arg(long)]
+        line_index: bool,
+
+        /// Skip tests already completed by an interrupted run in the same output directory
+        #[arg(long)]
+        resume: bool,
+
+        /// Also report the coverage of another workspace member or path dependency by the
+        /// packages' tests, without running its own tests (can be repeated)
+        #[arg(long, value_name = "CRATE")]
+        include: Vec<String>,
+
+        /// Only run shard i of n (e.g. 2/4), saving raw coverage to the report path for
+        /// 'isotarp merge' instead of an analysis, which is where any exports are written
+        #[arg(
+            long,
+            value_name = "I/N",
+            conflicts_with_all = ["line_index", "lcov", "html", "cobertura", "markdown", "csv", "tsv"]
+        )]
+        shard: Option<Shard>,
+
+        /// Rebuild the analysis from tarpaulin reports already saved under this directory,
+        /// laid out as in the output directory, instead of running any tests
+        #[arg(long, value_name = "DIR", conflicts_with_all = ["shard", "resume"])]
+        from_reports: Option<PathBuf>,
+
+        #[command(flatten)]
+        exports: ExportArgs,
+    },
+
+    /// Find a minimal subset of tests that preserves the full suite's coverage
+    Minimize {
+        #[command(flatten)]
+        packages: PackageArgs,
+
+        /// Specific tests to consider (if not provided, all tests will be considered)
+        #[arg(short, long)]
+        tests: Option<Vec<String>>,
+
+        /// Output directory for intermediate results
+        #[arg(short, long, default_value = "isotarp-output")]
+        output_dir: PathBuf,
+
+        /// Output file for the kept and droppable tests (printed only if not provided)
+        #[arg(short, long)]
+        report: Option<PathBuf>,
+
+        #[command(flatten)]
+        collect: CollectArgs,
+
+        /// Rebuild the coverage from tarpaulin reports already saved under this directory,
+        /// laid out as in the output directory, instead of running any tests
+        #[arg(long, value_name = "DIR")]
+        from_reports: Option<PathBuf>,
+    },
+
+    /// Compare tests pairwise by covered lines and cluster near-duplicates
+    Similarity {
+        #[command(flatten)]
+        packages: PackageArgs,
+
+        /// Specific tests to compare (if not provided, all tests will be compared)
+        #[arg(short, long)]
+        tests: Option<Vec<String>>,
+
+        /// Output directory for intermediate results
+        #[arg(short, long, default_value = "isotarp-output")]
+        output_dir: PathBuf,
+
+        /// Output file for the similarity matrices and clusters
+        #[arg(short, long, default_value = "isotarp-similarity.json")]
+        report: PathBuf,
+
+        /// Similarity measure used for clustering
+        #[arg(long, default_value_t = SimilarityMetric::default(), value_name = "METRIC")]
+        metric: SimilarityMetric,
+
+        /// Minimum similarity (0.0 to 1.0) for two tests to share a cluster
+        #[arg(long, default_value_t = 0.9)]
+        threshold: f64,
+
+        #[command(flatten)]
+        collect: CollectArgs,
+
+        /// Rebuild the coverage from tarpaulin reports already saved under this directory,
+        /// laid out as in the output directory, instead of running any tests
+        #[arg(long, value_name = "DIR")]
+        from_reports: Option<PathBuf>,
+    },
+
+    /// List the tests covering lines changed since a git revision, using a saved analysis
+    Affected {
+        /// Analysis report to read coverage from
+        #[arg(short, long, default_value = "isotarp-analysis.json")]
+        report: PathBuf,
+
+        /// Git revision to diff the working tree against
+        #[arg(short, long, default_value = "HEAD")]
+        base: String,
+
+        /// Output file for the affected test names, one per line
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Compare two saved analyses and report tests and lines whose coverage changed
+    Diff {
+        /// Earlier analysis report
+        old: PathBuf,
+
+        /// Later analysis report
+        new: PathBuf,
+
+        /// Output file for the differences as JSON
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Exit with an error if any line lost its last covering test
+        #[arg(long)]
+        fail_on_lost: bool,
+    },
+
+    /// Merge the raw coverage of analysis shards and compute uniqueness across all of them
+    Merge {
+        /// Shard reports written by 'isotarp analyze --shard'
+        #[arg(required = true)]
+        parts: Vec<PathBuf>,
+
+        /// Output file for the analysis result
+        #[arg(short, long, default_value = "isotarp-analysis.json")]
+        report: PathBuf,
+
+        /// Include, per file, every covered line and the tests that cover it
+        #[arg(long)]
+        line_index: bool,
+
+        #[command(flatten)]
+        exports: ExportArgs,
     },
 }
 
-pub fn execute_list_command(package: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let tests = list_tests(package)?;
-    println!("Found {} tests in package '{}':", tests.len(), package);
+pub fn execute_list_command(
+    packages: &[String],
+    doctests: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let tests = list_tests(packages, doctests)?;
+    println!(
+        "Found {} tests in package '{}':",
+        tests.len(),
+        packages_label(packages)
+    );
     for test in tests {
         println!("  {}", test);
     }
     Ok(())
 }
 
-// Updated execute_analyze_command function
-pub fn execute_analyze_command(
-    package: &str,
+/// Resolve the requested test patterns against the tests available in the packages
+fn select_tests(
+    packages: &[String],
     tests: Option<Vec<String>>,
-    output_dir: &Path,
-    report: &Path,
-    target_mode: TargetMode,
-) -> Result<(), Box<dyn std::error::Error>> {
-    // Create the output directory if it doesn't exist
-    std::fs::create_dir_all(output_dir)?;
-
-    let available_tests = list_tests(package)?;
+    doctests: bool,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    filter_tests(list_tests(packages, doctests)?, tests)
+}
 
+/// Resolve the requested test patterns against a list of available tests
+fn filter_tests(
+    available_tests: Vec<String>,
+    tests: Option<Vec<String>>,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
     let test_names = match tests {
         Some(specified_tests) => {
             let (selected_tests, invalid_patterns) =
@@ -96,36 +262,377 @@ pub fn execute_analyze_command(
         }
         None => {
             println!("No specific tests provided, analyzing all tests...");
-            available_tests
+            available_tests.clone()
         }
     };
 
+    Ok(test_names)
+}
+
+/// Warn about selected tests that prefix other tests
+///
+/// Tests are run with exact name matching, but saved reports may have been gathered by other
+/// tools or older versions of isotarp without it, and then include the tests they prefix.
+fn warn_prefix_collisions(available_tests: &[String], test_names: &[String]) {
+    let collisions = find_prefix_collisions(available_tests, test_names);
+    if !collisions.is_empty() {
+        println!(
+            "Warning: The following tests are prefixes of other tests, so their saved reports may include them unless they were run with exact matching:"
+        );
+        for (test_name, prefixed) in &collisions {
+            println!("  {} (prefix of {})", test_name, prefixed.join(", "));
+        }
+    }
+}
+
+/// Packages whose tests are run and whose code is measured
+#[derive(Args, Debug, Default, Clone)]
+pub struct PackageArgs {
+    /// Package name (repeat to analyze several packages together)
+    #[arg(
+        short,
+        long = "package",
+        value_name = "PACKAGE",
+        required_unless_present = "workspace"
+    )]
+    pub package: Vec<String>,
+
+    /// Use every member of the current workspace as the packages
+    #[arg(long, conflicts_with = "package")]
+    pub workspace: bool,
+}
+
+impl PackageArgs {
+    /// Get the names of the selected packages
+    pub fn resolve(&self) -> Result<Vec<String>, Error> {
+        if self.workspace {
+            workspace_members()
+        } else {
+            Ok(self.package.clone())
+        }
+    }
+}
+
+/// How tests are run to collect their coverage
+#[derive(Args, Debug, Default, Clone)]
+pub struct CollectArgs {
+    /// Target directory mode: "per" creates a separate target dir for each test (default),
+    /// "one" reuses a single target dir sequentially (saves disk space but may be slower)
+    #[arg(short = 'm', long, default_value_t = TargetMode::default(), value_name="MODE")]
+    pub target_mode: TargetMode,
+
+    /// Rerun every test instead of reusing coverage cached by earlier runs
+    #[arg(long)]
+    pub no_cache: bool,
+
+    /// Include the package's doctests, each run on its own through tarpaulin's doc mode
+    #[arg(long)]
+    pub doctests: bool,
+}
+
+impl CollectArgs {
+    /// Options to collect coverage with, neither recording nor resuming progress
+    pub fn options(&self) -> CollectOptions {
+        CollectOptions {
+            target_mode: self.target_mode,
+            use_cache: !self.no_cache,
+            doctests: self.doctests,
+            ..Default::default()
+        }
+    }
+}
+
+/// Additional formats to write an analysis in
+#[derive(Args, Debug, Default, Clone)]
+pub struct ExportArgs {
+    /// Write an LCOV tracefile with one test name section per test
+    #[arg(long, value_name = "PATH")]
+    pub lcov: Option<PathBuf>,
+
+    /// Write a self-contained HTML report of the source annotated by covering tests
+    #[arg(long, value_name = "PATH")]
+    pub html: Option<PathBuf>,
+
+    /// Write a Cobertura XML report of all tests' coverage, with each line's covering tests
+    #[arg(long, value_name = "PATH")]
+    pub cobertura: Option<PathBuf>,
+
+    /// Write the summary as GitHub-flavoured Markdown, e.g. for a pull request comment
+    #[arg(long, value_name = "PATH")]
+    pub markdown: Option<PathBuf>,
+
+    /// Include per-file breakdowns in the Markdown summary
+    #[arg(long, requires = "markdown")]
+    pub markdown_files: bool,
+
+    /// Write the test × file coverage matrix as CSV, and each covered line to a sibling "-lines" file
+    #[arg(long, value_name = "PATH")]
+    pub csv: Option<PathBuf>,
+
+    /// Write the test × file coverage matrix as TSV, and each covered line to a sibling "-lines" file
+    #[arg(long, value_name = "PATH")]
+    pub tsv: Option<PathBuf>,
+}
+
+/// Options of the analyze command that change what it produces, rather than how it runs tests
+#[derive(Debug, Default, Clone)]
+pub struct AnalyzeOptions {
+    /// Include, per file, every covered line and the tests that cover it
+    pub line_index: bool,
+    /// Only collect the raw coverage of one shard of the tests
+    pub shard: Option<Shard>,
+    /// Rebuild the analysis from tarpaulin reports saved under this directory
+    pub from_reports: Option<PathBuf>,
+    pub exports: ExportArgs,
+}
+
+/// Options of the similarity command that change what it compares, rather than how it runs tests
+#[derive(Debug, Clone)]
+pub struct SimilarityOptions {
+    pub metric: SimilarityMetric,
+    /// Minimum similarity for two tests to share a cluster
+    pub threshold: f64,
+    /// Compare the tests of tarpaulin reports saved under this directory
+    pub from_reports: Option<PathBuf>,
+}
+
+/// Collect per-test coverage, cleaning up target directories in case of error
+fn collect_with_cleanup(
+    packages: &[String],
+    test_names: &[String],
+    output_dir: &Path,
+    options: &CollectOptions,
+) -> Result<CollectedCoverage, Box<dyn std::error::Error>> {
+    match collect_test_coverage(packages, test_names, output_dir, options) {
+        Ok(test_coverage) => Ok(test_coverage),
+        Err(e) => {
+            cleanup_target_dirs(output_dir, test_names);
+            Err(Box::new(e))
+        }
+    }
+}
+
+// Updated execute_analyze_command function
+pub fn execute_analyze_command(
+    packages: &[String],
+    tests: Option<Vec<String>>,
+    output_dir: &Path,
+    report: &Path,
+    options: CollectOptions,
+    analyze_options: AnalyzeOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let AnalyzeOptions {
+        line_index,
+        shard,
+        from_reports,
+        exports,
+    } = analyze_options;
+
+    if let Some(reports_dir) = from_reports {
+        return execute_analyze_reports(
+            packages,
+            &options.include,
+            tests,
+            &reports_dir,
+            report,
+            line_index,
+            &exports,
+        );
+    }
+    let package = packages_label(packages);
+
+    // Create the output directory if it doesn't exist
+    std::fs::create_dir_all(output_dir)?;
+
+    let test_names = select_tests(packages, tests, options.doctests)?;
+
+    if let Some(shard) = shard {
+        return execute_analyze_shard(packages, &test_names, output_dir, report, options, shard);
+    }
+
     println!(
         "Analyzing {} tests in package '{}' using target mode: {}",
         test_names.len(),
         package,
-        target_mode
+        options.target_mode
     );
 
-    // Run the analysis with cleanup in case of error
-    let result = run_analysis(package, &test_names, output_dir, target_mode);
-
-    // Handle the result
-    let analysis = match result {
-        Ok(analysis) => analysis,
-        Err(e) => {
-            // Do a final cleanup in case there wasn't one
-            cleanup_target_dirs(output_dir, &test_names);
-            return Err(Box::new(e));
-        }
-    };
+    // Keep the raw coverage alongside the analysis, for the export formats that need it
+    let coverage = collect_with_cleanup(packages, &test_names, output_dir, &options)?;
+    let analysis = build_analysis(&package, &coverage, line_index);
 
     // Save the analysis result
     save_analysis(&analysis, report)?;
 
     println!("Analysis complete! Results saved to {}", report.display());
 
-    // Print a summary of the results
+    write_exports(&exports, &coverage, &analysis)?;
+
+    print_analysis_summary(&analysis);
+
+    // Final cleanup just to be extra sure
+    cleanup_target_dirs(output_dir, &test_names);
+
+    Ok(())
+}
+
+/// Rebuild the coverage of the selected tests from saved tarpaulin reports, returning it
+/// along with the selected test names
+fn coverage_from_reports(
+    packages: &[String],
+    include: &[String],
+    tests: Option<Vec<String>>,
+    reports_dir: &Path,
+) -> Result<(CollectedCoverage, Vec<String>), Box<dyn std::error::Error>> {
+    let mut coverage = collect_from_reports(&package_roots(packages, include)?, reports_dir)?;
+
+    let mut available_tests: Vec<String> = coverage.tests.keys().cloned().collect();
+    available_tests.sort();
+    if available_tests.is_empty() {
+        return Err(format!("No tarpaulin reports found under {}", reports_dir.display()).into());
+    }
+
+    let test_names = filter_tests(available_tests.clone(), tests)?;
+    warn_prefix_collisions(&available_tests, &test_names);
+    coverage
+        .tests
+        .retain(|test_name, _| test_names.contains(test_name));
+    coverage
+        .hits
+        .retain(|test_name, _| test_names.contains(test_name));
+
+    Ok((coverage, test_names))
+}
+
+/// Rebuild an analysis from saved tarpaulin reports, without running any tests
+fn execute_analyze_reports(
+    packages: &[String],
+    include: &[String],
+    tests: Option<Vec<String>>,
+    reports_dir: &Path,
+    report: &Path,
+    line_index: bool,
+    exports: &ExportArgs,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let package = packages_label(packages);
+    let (coverage, test_names) = coverage_from_reports(packages, include, tests, reports_dir)?;
+
+    println!(
+        "Analyzing {} tests in package '{}' from reports in {}",
+        test_names.len(),
+        package,
+        reports_dir.display()
+    );
+
+    let analysis = build_analysis(&package, &coverage, line_index);
+
+    save_analysis(&analysis, report)?;
+
+    println!("Analysis complete! Results saved to {}", report.display());
+
+    write_exports(exports, &coverage, &analysis)?;
+
+    print_analysis_summary(&analysis);
+
+    Ok(())
+}
+
+/// Write the analysis in each additional format requested
+fn write_exports(
+    exports: &ExportArgs,
+    coverage: &CollectedCoverage,
+    analysis: &IsotarpAnalysis,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(path) = &exports.lcov {
+        std::fs::write(path, render_lcov(coverage, analysis))?;
+        println!("LCOV tracefile saved to {}", path.display());
+    }
+
+    if let Some(path) = &exports.html {
+        std::fs::write(path, render_html(coverage, analysis))?;
+        println!("HTML report saved to {}", path.display());
+    }
+
+    if let Some(path) = &exports.cobertura {
+        let root = workspace_root()?;
+        std::fs::write(path, render_cobertura(coverage, analysis, &root))?;
+        println!("Cobertura report saved to {}", path.display());
+    }
+
+    if let Some(path) = &exports.markdown {
+        std::fs::write(path, render_markdown(analysis, exports.markdown_files))?;
+        println!("Markdown summary saved to {}", path.display());
+    }
+
+    for (path, delimiter) in [(&exports.csv, ','), (&exports.tsv, '\t')] {
+        if let Some(path) = path {
+            let lines_path = line_table_path(path);
+            std::fs::write(path, render_coverage_table(analysis, delimiter))?;
+            std::fs::write(
+                &lines_path,
+                render_line_table(coverage, analysis, delimiter),
+            )?;
+            println!(
+                "Coverage tables saved to {} and {}",
+                path.display(),
+                lines_path.display()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Collect the raw coverage of one shard of the tests, to be merged into a full analysis later
+fn execute_analyze_shard(
+    packages: &[String],
+    test_names: &[String],
+    output_dir: &Path,
+    report: &Path,
+    options: CollectOptions,
+    shard: Shard,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let package = packages_label(packages);
+    let shard_names = shard_tests(test_names, shard);
+
+    println!(
+        "Collecting shard {} ({} of {} tests) in package '{}' using target mode: {}",
+        shard,
+        shard_names.len(),
+        test_names.len(),
+        package,
+        options.target_mode
+    );
+
+    let coverage = collect_with_cleanup(packages, &shard_names, output_dir, &options)?;
+
+    let mut tests = test_names.to_vec();
+    tests.sort();
+    let partial = PartialCoverage {
+        schema_version: SCHEMA_VERSION,
+        package,
+        shard,
+        tests,
+        doctests: options.doctests,
+        coverage,
+    };
+    save_partial(&partial, report)?;
+
+    println!(
+        "Shard {} complete! Raw coverage saved to {}",
+        shard,
+        report.display()
+    );
+    println!("Combine all {} shards with 'isotarp merge'", shard.count);
+
+    // Final cleanup just to be extra sure
+    cleanup_target_dirs(output_dir, &shard_names);
+
+    Ok(())
+}
+
+/// Print the per-test and per-file summary of an analysis
+fn print_analysis_summary(analysis: &IsotarpAnalysis) {
     let tests_by_unique: Vec<_> = analysis.tests.iter().collect();
 
     // Separate tests into categories
@@ -145,7 +652,7 @@ pub fn execute_analyze_command(
 
     // Sort tests with unique coverage by number of unique lines (descending)
     tests_with_unique_coverage
-        .sort_by(|a, b| b.1.unique_covered_lines.cmp(&a.1.unique_covered_lines));
+        .sort_by_key(|(_, stats)| std::cmp::Reverse(stats.unique_covered_lines));
 
     // Display tests with unique coverage
     if !tests_with_unique_coverage.is_empty() {
@@ -157,6 +664,21 @@ pub fn execute_analyze_command(
                 "  {}: {} unique lines ({:.1}% of {} total covered lines)",
                 test_name, stats.unique_covered_lines, unique_pct, stats.total_covered_lines
             );
+            if stats.unique_functions > 0 {
+                let mut unique_functions: Vec<String> = stats
+                    .files
+                    .iter()
+                    .flat_map(|(file, file_stats)| {
+                        file_stats
+                            .functions
+                            .iter()
+                            .filter(|(_, function)| function.only_covering_test)
+                            .map(move |(name, _)| format!("{}::{}", file, name))
+                    })
+                    .collect();
+                unique_functions.sort();
+                println!("    only test reaching: {}", unique_functions.join(", "));
+            }
         }
     }
 
@@ -174,6 +696,20 @@ pub fn execute_analyze_command(
                 "  {}: 0 unique lines (covers {} total lines)",
                 test_name, stats.total_covered_lines
             );
+            if !stats.subsumed_by.is_empty() {
+                let subsuming: Vec<String> = stats
+                    .subsumed_by
+                    .iter()
+                    .map(|other| {
+                        if other.equal {
+                            format!("{} (identical)", other.test)
+                        } else {
+                            other.test.clone()
+                        }
+                    })
+                    .collect();
+                println!("    subsumed by: {}", subsuming.join(", "));
+            }
         }
     }
 
@@ -185,8 +721,471 @@ pub fn execute_analyze_command(
         }
     }
 
+    // Display tests that exercise shared lines more heavily than any other test
+    let mut tests_by_dominance: Vec<_> = analysis
+        .tests
+        .iter()
+        .filter(|(_, stats)| stats.dominant_lines > 0)
+        .collect();
+    tests_by_dominance.sort_by_key(|(test_name, stats)| {
+        (std::cmp::Reverse(stats.dominant_lines), test_name.as_str())
+    });
+
+    if !tests_by_dominance.is_empty() {
+        println!("\nTests with the most hits on shared lines:");
+        for (test_name, stats) in &tests_by_dominance {
+            println!(
+                "  {}: dominant on {} shared lines ({} hits in total)",
+                test_name, stats.dominant_lines, stats.total_hits
+            );
+        }
+    }
+
+    // Display coverable lines that no test reaches
+    let totals = &analysis.totals;
+    if totals.coverable_lines > 0 {
+        let covered_pct = (totals.covered_lines as f64 / totals.coverable_lines as f64) * 100.0;
+        println!(
+            "\nOverall: {} of {} coverable lines covered ({:.1}%), {} uncovered",
+            totals.covered_lines, totals.coverable_lines, covered_pct, totals.uncovered_lines
+        );
+    }
+
+    let mut files_with_uncovered: Vec<_> = analysis
+        .files
+        .iter()
+        .filter(|(_, summary)| !summary.uncovered_lines.is_empty())
+        .collect();
+    files_with_uncovered.sort_by_key(|(file, _)| *file);
+
+    if !files_with_uncovered.is_empty() {
+        println!("\nUncovered lines by file:");
+        for (file, summary) in files_with_uncovered {
+            println!(
+                "  {}: {} of {} coverable lines uncovered ({})",
+                file,
+                summary.uncovered_lines.len(),
+                summary.coverable_lines,
+                format_line_ranges(&summary.uncovered_lines)
+            );
+        }
+    }
+
+    // Display what the tests of other crates add to each crate's coverage
+    if !analysis.crates.is_empty() {
+        println!("\nCoverage by crate:");
+        for (crate_name, summary) in &analysis.crates {
+            let Some(missed_lines) = &summary.missed_by_own_tests else {
+                println!(
+                    "  {}: {} of {} coverable lines covered, by tests of other crates only",
+                    crate_name, summary.covered_lines, summary.coverable_lines
+                );
+                continue;
+            };
+            let missed: usize = missed_lines.values().map(Vec::len).sum();
+            println!(
+                "  {}: {} of {} coverable lines covered, {} reached only by tests of other crates",
+                crate_name, summary.covered_lines, summary.coverable_lines, missed
+            );
+
+            let mut outside_tests: Vec<_> = summary
+                .tests
+                .iter()
+                .filter_map(|(test_name, stats)| {
+                    let missed = stats.missed_by_own_tests.filter(|missed| *missed > 0)?;
+                    Some((test_name, missed, stats.unique_lines))
+                })
+                .collect();
+            outside_tests.sort_by_key(|(test_name, missed, _)| {
+                (std::cmp::Reverse(*missed), test_name.as_str())
+            });
+            for (test_name, missed, unique_lines) in outside_tests {
+                println!(
+                    "    {}: {} lines the crate's own tests miss ({} unique)",
+                    test_name, missed, unique_lines
+                );
+            }
+        }
+    }
+}
+
+pub fn execute_minimize_command(
+    packages: &[String],
+    tests: Option<Vec<String>>,
+    output_dir: &Path,
+    report: Option<&Path>,
+    options: CollectOptions,
+    from_reports: Option<&Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (test_coverage, test_names) = match from_reports {
+        Some(reports_dir) => {
+            let (test_coverage, test_names) =
+                coverage_from_reports(packages, &options.include, tests, reports_dir)?;
+            println!(
+                "Minimizing {} tests in package '{}' from reports in {}",
+                test_names.len(),
+                packages_label(packages),
+                reports_dir.display()
+            );
+            (test_coverage, test_names)
+        }
+        None => {
+            // Create the output directory if it doesn't exist
+            std::fs::create_dir_all(output_dir)?;
+
+            let test_names = select_tests(packages, tests, options.doctests)?;
+
+            println!(
+                "Minimizing {} tests in package '{}' using target mode: {}",
+                test_names.len(),
+                packages_label(packages),
+                options.target_mode
+            );
+
+            let test_coverage = collect_with_cleanup(packages, &test_names, output_dir, &options)?;
+            (test_coverage, test_names)
+        }
+    };
+
+    let minimization = minimize_test_suite(&test_coverage.tests);
+
+    if let Some(report) = report {
+        let json = serde_json::to_string_pretty(&minimization)?;
+        std::fs::write(report, json)?;
+        println!(
+            "Minimization complete! Results saved to {}",
+            report.display()
+        );
+    }
+
+    println!(
+        "\nKeep {} of {} tests to cover all {} lines:",
+        minimization.kept.len(),
+        test_names.len(),
+        minimization.total_covered_lines
+    );
+    for kept in &minimization.kept {
+        println!("  {}: +{} lines", kept.name, kept.new_covered_lines);
+    }
+
+    if !minimization.droppable.is_empty() {
+        println!("\nTests that can be dropped without losing coverage:");
+        for test_name in &minimization.droppable {
+            println!("  {}", test_name);
+        }
+    }
+
     // Final cleanup just to be extra sure
-    cleanup_target_dirs(output_dir, &test_names);
+    if from_reports.is_none() {
+        cleanup_target_dirs(output_dir, &test_names);
+    }
+
+    Ok(())
+}
+
+pub fn execute_similarity_command(
+    packages: &[String],
+    tests: Option<Vec<String>>,
+    output_dir: &Path,
+    report: &Path,
+    options: CollectOptions,
+    similarity_options: SimilarityOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let SimilarityOptions {
+        metric,
+        threshold,
+        from_reports,
+    } = similarity_options;
+
+    if !(0.0..=1.0).contains(&threshold) {
+        return Err(format!(
+            "Similarity threshold must be between 0 and 1, got {}",
+            threshold
+        )
+        .into());
+    }
+
+    let (test_coverage, test_names) = match from_reports.as_deref() {
+        Some(reports_dir) => {
+            let (test_coverage, test_names) =
+                coverage_from_reports(packages, &options.include, tests, reports_dir)?;
+            println!(
+                "Comparing {} tests in package '{}' from reports in {}",
+                test_names.len(),
+                packages_label(packages),
+                reports_dir.display()
+            );
+            (test_coverage, test_names)
+        }
+        None => {
+            // Create the output directory if it doesn't exist
+            std::fs::create_dir_all(output_dir)?;
+
+            let test_names = select_tests(packages, tests, options.doctests)?;
+
+            println!(
+                "Comparing {} tests in package '{}' using target mode: {}",
+                test_names.len(),
+                packages_label(packages),
+                options.target_mode
+            );
+
+            let test_coverage = collect_with_cleanup(packages, &test_names, output_dir, &options)?;
+            (test_coverage, test_names)
+        }
+    };
+
+    let similarity = compute_test_similarity(&test_coverage.tests, metric, threshold);
+
+    let json = serde_json::to_string_pretty(&similarity)?;
+    std::fs::write(report, json)?;
+    println!(
+        "Similarity analysis complete! Results saved to {}",
+        report.display()
+    );
+
+    if similarity.clusters.is_empty() {
+        println!(
+            "\nNo tests have {} similarity of at least {:.2}",
+            metric, threshold
+        );
+    } else {
+        println!(
+            "\nClusters of tests with {} similarity of at least {:.2}:",
+            metric, threshold
+        );
+        let matrix = match metric {
+            SimilarityMetric::Jaccard => &similarity.jaccard,
+            SimilarityMetric::Overlap => &similarity.overlap,
+        };
+        let position = |test_name: &String| {
+            similarity
+                .tests
+                .iter()
+                .position(|other| other == test_name)
+                .unwrap_or_default()
+        };
+        for (idx, cluster) in similarity.clusters.iter().enumerate() {
+            // Show each member's similarity to the first test in the cluster
+            println!("  Cluster {}:", idx + 1);
+            let first = position(&cluster[0]);
+            println!("    {}", cluster[0]);
+            for test_name in &cluster[1..] {
+                println!(
+                    "    {} ({:.2} vs {})",
+                    test_name,
+                    matrix[first][position(test_name)],
+                    cluster[0]
+                );
+            }
+        }
+    }
+
+    // Final cleanup just to be extra sure
+    if from_reports.is_none() {
+        cleanup_target_dirs(output_dir, &test_names);
+    }
+
+    Ok(())
+}
+
+pub fn execute_affected_command(
+    report: &Path,
+    base: &str,
+    output: Option<&Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let analysis = load_analysis(report)?;
+    let changes = parse_diff(&git_diff(base)?);
+
+    if changes.is_empty() {
+        println!("No changes since {}", base);
+    }
+
+    let repo_root = git_toplevel()?;
+    let affected = find_affected_tests(
+        &analysis,
+        &changes,
+        Path::new(&repo_root),
+        &workspace_root()?,
+    );
+
+    if !affected.unmapped_files.is_empty() {
+        println!("Warning: The following changed files are not in the analysis:");
+        for file in &affected.unmapped_files {
+            println!("  {}", file);
+        }
+    }
+
+    if !affected.function_level_files.is_empty() {
+        println!("Warning: The report has no line index, so these files were matched by function:");
+        for file in &affected.function_level_files {
+            println!("  {}", file);
+        }
+    }
+
+    if !affected.file_level_files.is_empty() {
+        println!(
+            "Warning: These files have changes outside any known function, so every test covering them was selected:"
+        );
+        for file in &affected.file_level_files {
+            println!("  {}", file);
+        }
+    }
+
+    if let Some(output) = output {
+        let mut list: String = affected
+            .tests
+            .iter()
+            .map(|test_name| format!("{}\n", test_name))
+            .collect();
+        if list.is_empty() {
+            list.push('\n');
+        }
+        std::fs::write(output, list)?;
+        println!("Affected tests saved to {}", output.display());
+    }
+
+    println!(
+        "\n{} of {} tests in package '{}' are affected by changes since {}:",
+        affected.tests.len(),
+        analysis.tests.len(),
+        analysis.package,
+        base
+    );
+    for test_name in &affected.tests {
+        println!("  {}", test_name);
+    }
+
+    Ok(())
+}
+
+pub fn execute_diff_command(
+    old: &Path,
+    new: &Path,
+    output: Option<&Path>,
+    fail_on_lost: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let old_analysis = load_analysis(old)?;
+    let new_analysis = load_analysis(new)?;
+
+    if old_analysis.package != new_analysis.package {
+        println!(
+            "Warning: Comparing analyses of different packages ('{}' and '{}')",
+            old_analysis.package, new_analysis.package
+        );
+    }
+
+    for (path, analysis) in [(old, &old_analysis), (new, &new_analysis)] {
+        if !analysis.has_line_index() {
+            println!(
+                "Warning: {} was produced without --line-index, so lines shared by several tests \
+                 are not reported when they lose coverage",
+                path.display()
+            );
+        }
+    }
+
+    let diff = diff_analyses(&old_analysis, &new_analysis);
+
+    if let Some(output) = output {
+        let json = serde_json::to_string_pretty(&diff)?;
+        std::fs::write(output, json)?;
+        println!("Differences saved to {}", output.display());
+    }
+
+    println!(
+        "\nComparing {} ({} tests) with {} ({} tests):",
+        old.display(),
+        old_analysis.tests.len(),
+        new.display(),
+        new_analysis.tests.len()
+    );
+
+    if !diff.added_tests.is_empty() {
+        println!("\nAdded tests:");
+        for test_name in &diff.added_tests {
+            println!("  {}", test_name);
+        }
+    }
+
+    if !diff.removed_tests.is_empty() {
+        println!("\nRemoved tests:");
+        for test_name in &diff.removed_tests {
+            println!("  {}", test_name);
+        }
+    }
+
+    if !diff.changed_tests.is_empty() {
+        println!("\nTests with changed coverage:");
+        for change in &diff.changed_tests {
+            println!(
+                "  {}: {} -> {} total lines, {} -> {} unique lines",
+                change.test,
+                change.old_total_covered_lines,
+                change.new_total_covered_lines,
+                change.old_unique_covered_lines,
+                change.new_unique_covered_lines
+            );
+        }
+    }
+
+    let lost_count: usize = diff.lost_lines.values().map(|lines| lines.len()).sum();
+    if lost_count == 0 {
+        println!("\nNo line lost its last covering test");
+        return Ok(());
+    }
+
+    println!("\nLines that lost their last covering test:");
+    for (file, lines) in &diff.lost_lines {
+        let line_numbers: Vec<u64> = lines.keys().copied().collect();
+        let mut tests: Vec<&String> = lines.values().flatten().collect();
+        tests.sort();
+        tests.dedup();
+        println!(
+            "  {}: {} (previously covered by {})",
+            file,
+            format_line_ranges(&line_numbers),
+            tests
+                .iter()
+                .map(|test_name| test_name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+
+    if fail_on_lost {
+        return Err(format!("{} lines lost their last covering test", lost_count).into());
+    }
+
+    Ok(())
+}
+
+pub fn execute_merge_command(
+    parts: &[PathBuf],
+    report: &Path,
+    line_index: bool,
+    exports: &ExportArgs,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let parts = parts
+        .iter()
+        .map(|part| load_partial(part))
+        .collect::<Result<Vec<_>, _>>()?;
+    let Some(package) = parts.first().map(|part| part.package.clone()) else {
+        return Err("No shard reports given".into());
+    };
+
+    println!("Merging {} shards of package '{}'", parts.len(), package);
+
+    let coverage = merge_partials(parts)?;
+    let analysis = build_analysis(&package, &coverage, line_index);
+
+    save_analysis(&analysis, report)?;
+
+    println!("Merge complete! Results saved to {}", report.display());
+
+    write_exports(exports, &coverage, &analysis)?;
+
+    print_analysis_summary(&analysis);
 
     Ok(())
 }
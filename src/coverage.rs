@@ -1,6 +1,23 @@
 pub mod analysis;
+pub mod cache;
+pub mod functions;
+pub mod merge;
+pub mod minimize;
+pub mod progress;
+pub mod similarity;
 pub mod tarpaulin;
 
 // Re-export main functions
-pub use analysis::{analyze_test_coverage, run_analysis};
-pub use tarpaulin::{extract_covered_lines, list_tests, run_isolated_test_coverage};
+pub use analysis::{
+    analyze_test_coverage, build_analysis, build_line_index, collect_test_coverage,
+    find_subsuming_tests, run_analysis,
+};
+pub use cache::CoverageCache;
+pub use functions::{FunctionSpan, find_function_spans};
+pub use merge::merge_partials;
+pub use minimize::minimize_test_suite;
+pub use similarity::compute_test_similarity;
+pub use tarpaulin::{
+    extract_coverable_lines, extract_covered_lines, extract_line_hits, extract_sources,
+    extract_test_coverage, list_tests, run_isolated_test_coverage,
+};
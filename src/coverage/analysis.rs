@@ -1,46 +1,646 @@
-use crate::coverage::tarpaulin::run_isolated_test_coverage;
+use crate::coverage::cache::CoverageCache;
+use crate::coverage::functions::{
+    FunctionSpan, attribute_functions, lines_by_function, map_functions, summarize_functions,
+};
+use crate::coverage::progress::{ProgressHeader, ProgressManifest, open_progress};
+use crate::coverage::tarpaulin::{
+    extract_sources, extract_test_coverage, reroot_report, run_isolated_test_coverage,
+};
 use crate::types::errors::Error;
 use crate::types::models::{
-    FileCoverageAnalysis, IsotarpAnalysis, TargetMode, TestCoverageAnalysis, TestCoverageResult,
+    CollectOptions, CollectedCoverage, CoverageMap, CoverageTotals, CrateSummary,
+    CrateTestCoverage, DominantTest, FileCoverageAnalysis, FileSummary, IsotarpAnalysis, LineHits,
+    SCHEMA_VERSION, SubsumingTest, TargetMode, TarpaulinReport, TestCoverage, TestCoverageAnalysis,
+    TestCoverageResult, TestId,
 };
 use crate::utils::cleanup::{cleanup_single_test_dir, cleanup_target_dirs};
+use crate::utils::metadata::{PackageRoots, package_roots};
+use crate::utils::paths::{test_name_from_path, test_report_path};
 use crate::utils::pipeline::TargetPipeline;
 use crate::utils::target_symlink::prepare_target_dirs;
 use rayon::ThreadPoolBuilder;
 use rayon::prelude::*;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::Path;
 use std::process::Command;
+use walkdir::WalkDir;
 
 /// Run all tests at once using tarpaulin and process the results
 pub fn run_analysis(
-    package_name: &str,
+    packages: &[String],
     test_names: &[String],
     output_dir: &std::path::Path,
-    target_mode: TargetMode,
+    options: &CollectOptions,
+    line_index: bool,
 ) -> Result<IsotarpAnalysis, Error> {
+    let test_coverage = collect_test_coverage(packages, test_names, output_dir, options)?;
+
+    Ok(build_analysis(
+        &packages_label(packages),
+        &test_coverage,
+        line_index,
+    ))
+}
+
+/// Name the packages an analysis covers, as recorded in its report
+/// Example: ["core", "cli"] -> "core,cli"
+pub fn packages_label(packages: &[String]) -> String {
+    packages.join(",")
+}
+
+/// Generate the complete analysis from the collected coverage data
+pub fn build_analysis(
+    package_name: &str,
+    coverage: &CollectedCoverage,
+    line_index: bool,
+) -> IsotarpAnalysis {
+    let mut index = build_line_index(&coverage.tests);
+    let mut dominant = find_dominant_tests(&coverage.hits);
+    let crates = analyze_crates(package_name, coverage, &index);
+
+    let mut tests = analyze_test_coverage(&coverage.tests);
+    apply_line_hits(&mut tests, &coverage.hits, &dominant, line_index);
+
+    // Attribute covered lines to the functions that enclose them
+    let functions = map_functions(&coverage.sources);
+    let function_tests = find_function_tests(&coverage.tests, &functions);
+    apply_functions(&mut tests, &coverage.tests, &functions, &function_tests);
+
+    // Files are reported if any test could reach them, even if none did
+    let mut all_files: HashSet<&String> = coverage.coverable.keys().collect();
+    all_files.extend(index.keys());
+    let all_files: Vec<String> = all_files.into_iter().cloned().collect();
+
+    let mut files = HashMap::new();
+    let mut totals = CoverageTotals::default();
+
+    for file in all_files {
+        let lines = index.remove(&file).unwrap_or_default();
+        let coverable = coverage.coverable.get(&file);
+
+        let mut uncovered_lines: Vec<u64> = coverable
+            .map(|coverable| {
+                coverable
+                    .iter()
+                    .filter(|line| !lines.contains_key(line))
+                    .copied()
+                    .collect()
+            })
+            .unwrap_or_default();
+        uncovered_lines.sort();
+
+        let covered: HashSet<u64> = lines.keys().copied().collect();
+        let file_functions = functions
+            .get(&file)
+            .map(|spans| {
+                let tests_by_function = function_tests.get(&file).cloned().unwrap_or_default();
+                summarize_functions(spans, coverable, &covered, &tests_by_function)
+            })
+            .unwrap_or_default();
+
+        let summary = FileSummary {
+            coverable_lines: coverable.map_or(lines.len(), HashSet::len) as u32,
+            covered_lines: lines.len() as u32,
+            uncovered_lines,
+            lines: line_index.then_some(lines),
+            dominant: line_index.then(|| dominant.remove(&file).unwrap_or_default()),
+            functions: file_functions,
+        };
+
+        totals.coverable_lines += summary.coverable_lines;
+        totals.covered_lines += summary.covered_lines;
+        totals.uncovered_lines += summary.uncovered_lines.len() as u32;
+
+        files.insert(file, summary);
+    }
+
+    IsotarpAnalysis {
+        schema_version: SCHEMA_VERSION,
+        package: package_name.to_string(),
+        tests,
+        files,
+        totals,
+        crates,
+    }
+}
+
+/// Name the crate a test belongs to, from its package or else the analyzed package
+fn test_crate(test_name: &str, package_name: &str) -> String {
+    test_name
+        .parse::<TestId>()
+        .ok()
+        .and_then(|test_id| test_id.package)
+        .unwrap_or_else(|| package_name.to_string())
+}
+
+/// Summarize the coverage of each crate whose files are reported
+///
+/// Uniqueness is computed within each crate, and for crates whose own tests were part of the
+/// run, covered lines that none of them reach are singled out, showing what the tests of
+/// other crates add to it. Crates only included for their coverage have nothing to compare
+/// against, so they are left without these.
+pub fn analyze_crates(
+    package_name: &str,
+    coverage: &CollectedCoverage,
+    index: &HashMap<String, BTreeMap<u64, Vec<String>>>,
+) -> BTreeMap<String, CrateSummary> {
+    let mut crates: BTreeMap<String, CrateSummary> = BTreeMap::new();
+    let test_crates: HashMap<&str, String> = coverage
+        .tests
+        .keys()
+        .map(|test_name| (test_name.as_str(), test_crate(test_name, package_name)))
+        .collect();
+    let tested_crates: HashSet<&str> = test_crates.values().map(String::as_str).collect();
+
+    for (file, crate_name) in &coverage.crates {
+        let tested = tested_crates.contains(crate_name.as_str());
+        let summary = crates
+            .entry(crate_name.clone())
+            .or_insert_with(|| CrateSummary {
+                missed_by_own_tests: tested.then(BTreeMap::new),
+                ..Default::default()
+            });
+        let lines = index.get(file);
+        summary.coverable_lines += coverage
+            .coverable
+            .get(file)
+            .map_or(lines.map_or(0, BTreeMap::len), HashSet::len)
+            as u32;
+
+        for (line, tests) in lines.into_iter().flatten() {
+            summary.covered_lines += 1;
+            let own = tests
+                .iter()
+                .any(|test_name| test_crates.get(test_name.as_str()) == Some(crate_name));
+            if let Some(missed) = summary.missed_by_own_tests.as_mut().filter(|_| !own) {
+                missed.entry(file.clone()).or_default().push(*line);
+            }
+
+            for test_name in tests {
+                let stats =
+                    summary
+                        .tests
+                        .entry(test_name.clone())
+                        .or_insert_with(|| CrateTestCoverage {
+                            missed_by_own_tests: tested.then_some(0),
+                            ..Default::default()
+                        });
+                stats.covered_lines += 1;
+                if tests.len() == 1 {
+                    stats.unique_lines += 1;
+                }
+                if let Some(missed) = stats.missed_by_own_tests.as_mut().filter(|_| !own) {
+                    *missed += 1;
+                }
+            }
+        }
+    }
+
+    for summary in crates.values_mut() {
+        for lines in summary
+            .missed_by_own_tests
+            .iter_mut()
+            .flat_map(BTreeMap::values_mut)
+        {
+            lines.sort();
+        }
+    }
+
+    crates
+}
+
+/// Find, per file, the test with strictly the most hits on each line shared by several tests
+pub fn find_dominant_tests(
+    hits: &HashMap<String, LineHits>,
+) -> HashMap<String, BTreeMap<u64, DominantTest>> {
+    // Gather every test's hits on each line
+    let mut line_tests = HashMap::new();
+    for (test_name, file_hits) in hits {
+        for (file, lines) in file_hits {
+            for (line, count) in lines {
+                line_tests
+                    .entry((file, *line))
+                    .or_insert_with(Vec::new)
+                    .push((test_name, *count));
+            }
+        }
+    }
+
+    let mut dominant: HashMap<String, BTreeMap<u64, DominantTest>> = HashMap::new();
+    for ((file, line), mut counts) in line_tests {
+        if counts.len() < 2 {
+            continue;
+        }
+
+        // Only a strict maximum is dominant, ties have no winner
+        counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        if counts[0].1 == counts[1].1 {
+            continue;
+        }
+
+        dominant.entry(file.clone()).or_default().insert(
+            line,
+            DominantTest {
+                test: counts[0].0.clone(),
+                hits: counts[0].1,
+                total_hits: counts.iter().map(|(_, count)| count).sum(),
+            },
+        );
+    }
+
+    dominant
+}
+
+/// Fill in each test's hit totals and dominant line counts from the collected hit counts
+fn apply_line_hits(
+    tests: &mut HashMap<String, TestCoverageAnalysis>,
+    hits: &HashMap<String, LineHits>,
+    dominant: &HashMap<String, BTreeMap<u64, DominantTest>>,
+    line_index: bool,
+) {
+    for (test_name, analysis_entry) in tests.iter_mut() {
+        let Some(file_hits) = hits.get(test_name) else {
+            continue;
+        };
+
+        for (file, file_analysis) in analysis_entry.files.iter_mut() {
+            let Some(lines) = file_hits.get(file) else {
+                continue;
+            };
+            file_analysis.total_hits = lines.values().sum();
+            if line_index {
+                file_analysis.line_hits = Some(lines.iter().map(|(l, h)| (*l, *h)).collect());
+            }
+            analysis_entry.total_hits += file_analysis.total_hits;
+        }
+    }
+
+    for file_dominant in dominant.values() {
+        for dominant_test in file_dominant.values() {
+            if let Some(analysis_entry) = tests.get_mut(&dominant_test.test) {
+                analysis_entry.dominant_lines += 1;
+            }
+        }
+    }
+}
+
+/// Find, per file, the tests reaching at least one line of each function
+pub fn find_function_tests<'a>(
+    results: &CoverageMap,
+    functions: &'a HashMap<String, Vec<FunctionSpan>>,
+) -> HashMap<String, HashMap<&'a str, Vec<String>>> {
+    let mut function_tests: HashMap<String, HashMap<&str, Vec<String>>> = HashMap::new();
+
+    for (test_name, file_lines) in results {
+        for (file, lines) in file_lines {
+            let Some(spans) = functions.get(file) else {
+                continue;
+            };
+            let file_entry = function_tests.entry(file.clone()).or_default();
+            for name in lines_by_function(spans, lines).into_keys() {
+                file_entry.entry(name).or_default().push(test_name.clone());
+            }
+        }
+    }
+
+    // Sort the covering tests for deterministic output
+    for file_entry in function_tests.values_mut() {
+        for tests in file_entry.values_mut() {
+            tests.sort();
+        }
+    }
+
+    function_tests
+}
+
+/// Fill in each test's per-file function coverage and count the functions only it reaches
+fn apply_functions(
+    tests: &mut HashMap<String, TestCoverageAnalysis>,
+    results: &CoverageMap,
+    functions: &HashMap<String, Vec<FunctionSpan>>,
+    function_tests: &HashMap<String, HashMap<&str, Vec<String>>>,
+) {
+    for (test_name, analysis_entry) in tests.iter_mut() {
+        let Some(file_lines) = results.get(test_name) else {
+            continue;
+        };
+
+        for (file, file_analysis) in analysis_entry.files.iter_mut() {
+            let (Some(spans), Some(lines), Some(tests_by_function)) = (
+                functions.get(file),
+                file_lines.get(file),
+                function_tests.get(file),
+            ) else {
+                continue;
+            };
+
+            let unique_lines: HashSet<u64> = file_analysis.unique_lines.iter().copied().collect();
+            file_analysis.functions =
+                attribute_functions(spans, lines, &unique_lines, tests_by_function);
+            analysis_entry.unique_functions += file_analysis
+                .functions
+                .values()
+                .filter(|function| function.only_covering_test)
+                .count() as u32;
+        }
+    }
+}
+
+/// Invert the coverage map into, per file, each covered line and the tests covering it
+pub fn build_line_index(results: &CoverageMap) -> HashMap<String, BTreeMap<u64, Vec<String>>> {
+    let mut index: HashMap<String, BTreeMap<u64, Vec<String>>> = HashMap::new();
+
+    for (test_name, file_lines) in results {
+        for (file, lines) in file_lines {
+            let file_index = index.entry(file.clone()).or_default();
+            for line in lines {
+                file_index.entry(*line).or_default().push(test_name.clone());
+            }
+        }
+    }
+
+    // Sort the covering tests for deterministic output
+    for file_index in index.values_mut() {
+        for tests in file_index.values_mut() {
+            tests.sort();
+        }
+    }
+
+    index
+}
+
+/// Run each test in isolation using tarpaulin and collect its covered lines per file
+pub fn collect_test_coverage(
+    packages: &[String],
+    test_names: &[String],
+    output_dir: &std::path::Path,
+    options: &CollectOptions,
+) -> Result<CollectedCoverage, Error> {
     // Create output directory
     std::fs::create_dir_all(output_dir)?;
 
+    let package_args: Vec<&str> = packages
+        .iter()
+        .flat_map(|package_name| ["-p", package_name.as_str()])
+        .collect();
+
     // Clean and build once at the beginning
     println!("Cleaning and building package...");
     let status = Command::new("cargo")
-        .args(["clean", "-p", package_name])
+        .arg("clean")
+        .args(&package_args)
         .status()?;
 
     if !status.success() {
         return Err(Error::CommandFailed("cargo clean".to_string()));
     }
 
-    // Build the package
+    // Build the packages
     let status = Command::new("cargo")
-        .args(["build", "--tests", "-p", package_name])
+        .args(["build", "--tests"])
+        .args(&package_args)
         .status()?;
 
     if !status.success() {
         return Err(Error::CommandFailed("cargo build --tests".to_string()));
     }
 
+    // Tell the packages' own files apart from those of dependencies and other packages
+    let roots = package_roots(packages, &options.include)?;
+
+    // Reuse the coverage of tests whose inputs are unchanged since they were cached
+    let mut cache = if options.use_cache {
+        Some(CoverageCache::open(packages, &roots, output_dir)?)
+    } else {
+        None
+    };
+    // Record tests as they complete, so an interrupted run can be resumed. Other commands
+    // sharing the output directory leave the manifest alone.
+    let (manifest, mut completed) = if options.record_progress || options.resume {
+        let header = ProgressHeader::new(
+            &packages_label(&roots.reported_crates()),
+            options.doctests,
+            test_names,
+        );
+        let (manifest, completed) = open_progress(output_dir, &header, options.resume)?;
+        (Some(manifest), completed)
+    } else {
+        (None, HashMap::new())
+    };
+    if options.resume {
+        println!(
+            "Resuming: {} tests already completed",
+            test_names
+                .iter()
+                .filter(|test_name| completed.contains_key(*test_name))
+                .count()
+        );
+    }
+
+    let mut collected_results = Vec::new();
+    let mut tests_to_run = Vec::new();
+    // Resumed tests are reported above, so only cache hits are counted here
+    let mut cached_count = 0;
+    for test_name in test_names {
+        if let Some(coverage) = completed.remove(test_name) {
+            collected_results.push((test_name.clone(), coverage));
+            continue;
+        }
+        match cache.as_mut().and_then(|cache| cache.lookup(test_name)) {
+            Some(coverage) => {
+                collected_results.push((test_name.clone(), coverage));
+                cached_count += 1;
+            }
+            None => tests_to_run.push(test_name.clone()),
+        }
+    }
+    if cache.is_some() {
+        println!(
+            "Reusing cached coverage for {} of {} tests",
+            cached_count,
+            test_names.len()
+        );
+    }
+
+    if !tests_to_run.is_empty() {
+        let fresh_results = run_tests_coverage(
+            packages,
+            &roots,
+            &tests_to_run,
+            output_dir,
+            options.target_mode,
+            manifest.as_ref(),
+        )?;
+        if let Some(cache) = cache.as_mut() {
+            for (test_name, coverage) in &fresh_results {
+                if let Err(e) = cache.store(test_name, coverage) {
+                    eprintln!(
+                        "Warning: Failed to cache coverage for test '{}': {}",
+                        test_name, e
+                    );
+                }
+            }
+        }
+        collected_results.extend(fresh_results);
+    }
+
+    let mut collected = merge_test_results(collected_results);
+    assign_crates(&mut collected, &roots);
+
+    // Every report embeds the same sources, so read them from the first one available
+    collected.sources = tests_to_run
+        .iter()
+        .find_map(|test_name| {
+            let report_content =
+                std::fs::read_to_string(test_report_path(output_dir, test_name)).ok()?;
+            let report = serde_json::from_str(&report_content).ok()?;
+            Some(extract_sources(&report, &roots))
+        })
+        .unwrap_or_default();
+
+    // If every test was cached there are no fresh reports, so read the sources from disk
+    if collected.sources.is_empty() {
+        collected.sources = collected
+            .coverable
+            .keys()
+            .filter_map(|file| {
+                let source = std::fs::read_to_string(roots.absolute_path(file)).ok()?;
+                Some((file.clone(), source))
+            })
+            .collect();
+    }
+
+    Ok(collected)
+}
+
+/// Convert per-test results into collected coverage, merging the coverable lines
+fn merge_test_results(results: Vec<TestCoverageResult>) -> CollectedCoverage {
+    let mut collected = CollectedCoverage::default();
+    for (test_name, coverage) in results {
+        for (file, lines) in coverage.coverable {
+            collected.coverable.entry(file).or_default().extend(lines);
+        }
+        collected.hits.insert(test_name.clone(), coverage.hits);
+        collected.tests.insert(test_name, coverage.covered);
+    }
+    collected
+}
+
+/// Record the crate each file belongs to, if the files of several crates are reported
+fn assign_crates(collected: &mut CollectedCoverage, roots: &PackageRoots) {
+    if !roots.spans_several_crates() {
+        return;
+    }
+    let files: HashSet<&String> = collected
+        .coverable
+        .keys()
+        .chain(collected.tests.values().flat_map(|files| files.keys()))
+        .collect();
+    collected.crates = files
+        .into_iter()
+        .filter_map(|file| Some((file.clone(), roots.crate_name(file)?.to_string())))
+        .collect();
+}
+
+/// Collect per-test coverage from tarpaulin reports already on disk, without running any tests
+///
+/// Reports are found anywhere under `reports_dir` in the layout isotarp writes them, i.e.
+/// `<reports_dir>/<module>/<path>/<test_name>/tarpaulin-report.json` for the test
+/// `module::path::test_name`. Reports produced in a checkout at another location, e.g. on CI,
+/// are moved to the local one, and it is an error if no report has any of the packages' files.
+pub fn collect_from_reports(
+    roots: &PackageRoots,
+    reports_dir: &Path,
+) -> Result<CollectedCoverage, Error> {
+    let mut results = Vec::new();
+    let mut sources = HashMap::new();
+
+    for entry in WalkDir::new(reports_dir).sort_by_file_name() {
+        let entry = entry.map_err(|e| {
+            Error::Io(std::io::Error::other(format!(
+                "Failed to walk reports directory '{}': {}",
+                reports_dir.display(),
+                e
+            )))
+        })?;
+        if !entry.file_type().is_file() || entry.file_name() != "tarpaulin-report.json" {
+            continue;
+        }
+
+        // The directories between the root and the report spell out the test name
+        let Some(test_name) = entry
+            .path()
+            .parent()
+            .and_then(|dir| dir.strip_prefix(reports_dir).ok())
+            .and_then(test_name_from_path)
+        else {
+            eprintln!(
+                "Warning: Skipping report '{}' outside any test directory",
+                entry.path().display()
+            );
+            continue;
+        };
+
+        let report_content = std::fs::read_to_string(entry.path())?;
+        let mut report: TarpaulinReport = match serde_json::from_str(&report_content) {
+            Ok(report) => report,
+            Err(e) => {
+                eprintln!(
+                    "Warning: Skipping unreadable report '{}': {}",
+                    entry.path().display(),
+                    e
+                );
+                continue;
+            }
+        };
+
+        reroot_report(&mut report, roots);
+
+        // Every report embeds the same sources, so read them from the first one
+        if sources.is_empty() {
+            sources = extract_sources(&report, roots);
+        }
+        results.push((test_name, extract_test_coverage(&report, roots)));
+    }
+
+    let matched = results
+        .iter()
+        .any(|(_, coverage)| !coverage.coverable.is_empty());
+    if !results.is_empty() && !matched {
+        return Err(Error::ReportMismatch(format!(
+            "none of the files in the reports under '{}' belong to {}",
+            reports_dir.display(),
+            roots.reported_crates().join(", ")
+        )));
+    }
+
+    let mut collected = merge_test_results(results);
+    collected.sources = sources;
+    assign_crates(&mut collected, roots);
+    Ok(collected)
+}
+
+/// Run each test in isolation under tarpaulin, using the target directory mode given
+fn run_tests_coverage(
+    packages: &[String],
+    roots: &PackageRoots,
+    test_names: &[String],
+    output_dir: &Path,
+    target_mode: TargetMode,
+    manifest: Option<&ProgressManifest>,
+) -> Result<Vec<TestCoverageResult>, Error> {
+    let record = |test_name: &str, coverage: &TestCoverage| {
+        let Some(manifest) = manifest else {
+            return;
+        };
+        if let Err(e) = manifest.record(test_name, coverage) {
+            eprintln!(
+                "Warning: Failed to record progress for test '{}': {}",
+                test_name, e
+            );
+        }
+    };
+
     // Get the master target directory
     let master_target_dir = Path::new("target");
 
@@ -48,7 +648,7 @@ pub fn run_analysis(
     let total_tests = test_names.len();
 
     // Collect the results
-    let collected_results: Vec<(String, HashMap<String, HashSet<u64>>)>;
+    let collected_results: Vec<TestCoverageResult>;
 
     match target_mode {
         TargetMode::Per => {
@@ -102,11 +702,7 @@ pub fn run_analysis(
 
                         println!("Running coverage for test: {}", test_name);
                         let result = run_isolated_test_coverage(
-                            package_name,
-                            test_name,
-                            output_dir,
-                            target_dir,
-                            true,
+                            packages, roots, test_name, output_dir, target_dir, true,
                         );
 
                         // Immediate cleanup regardless of success or failure
@@ -114,7 +710,10 @@ pub fn run_analysis(
 
                         // Return the result paired with the test name
                         match result {
-                            Ok(covered_lines) => Ok((test_name.clone(), covered_lines)),
+                            Ok(coverage) => {
+                                record(test_name, &coverage);
+                                Ok((test_name.clone(), coverage))
+                            }
                             Err(e) => {
                                 eprintln!("Error running test {}: {}", test_name, e);
                                 Err(e)
@@ -179,14 +778,16 @@ pub fn run_analysis(
                 // Run test coverage
                 println!("Running coverage for test: {}", test_name);
                 match run_isolated_test_coverage(
-                    package_name,
+                    packages,
+                    roots,
                     test_name,
                     output_dir,
                     &target_dir,
                     true,
                 ) {
-                    Ok(covered_lines) => {
-                        results_vec.push((test_name.clone(), covered_lines));
+                    Ok(coverage) => {
+                        record(test_name, &coverage);
+                        results_vec.push((test_name.clone(), coverage));
                     }
                     Err(e) => {
                         eprintln!("Error running test {}: {}", test_name, e);
@@ -210,19 +811,7 @@ pub fn run_analysis(
         }
     }
 
-    // Convert the collected results into a HashMap
-    let mut test_coverage = HashMap::new();
-    for (test_name, coverage) in collected_results {
-        test_coverage.insert(test_name, coverage);
-    }
-
-    // Generate analysis from the collected coverage data
-    let analysis = analyze_test_coverage(&test_coverage);
-
-    Ok(IsotarpAnalysis {
-        package: package_name.to_string(),
-        tests: analysis,
-    })
+    Ok(collected_results)
 }
 
 /// Analyze coverage to find unique lines covered by each test
@@ -232,6 +821,8 @@ pub fn analyze_test_coverage(
     // The rest of this function remains unchanged...
     let mut analysis = HashMap::new();
 
+    let mut subsumptions = find_subsuming_tests(results);
+
     // Get all files and lines
     let mut all_files = HashSet::new();
     for file_lines in results.values() {
@@ -244,6 +835,10 @@ pub fn analyze_test_coverage(
             total_covered_lines: 0,
             unique_covered_lines: 0,
             files: HashMap::new(),
+            subsumed_by: subsumptions.remove(test_name).unwrap_or_default(),
+            total_hits: 0,
+            dominant_lines: 0,
+            unique_functions: 0,
         };
 
         // For each file
@@ -287,6 +882,9 @@ pub fn analyze_test_coverage(
                         total_covered_lines: total_lines,
                         unique_covered_lines: unique_count,
                         unique_lines: unique_lines.into_iter().collect(),
+                        total_hits: 0,
+                        line_hits: None,
+                        functions: BTreeMap::new(),
                     },
                 );
             }
@@ -297,3 +895,48 @@ pub fn analyze_test_coverage(
 
     analysis
 }
+
+/// Flatten a test's per-file coverage into a set of (file, line) pairs
+pub(crate) fn flatten_lines(file_lines: &HashMap<String, HashSet<u64>>) -> HashSet<(&str, u64)> {
+    file_lines
+        .iter()
+        .flat_map(|(file, lines)| lines.iter().map(move |line| (file.as_str(), *line)))
+        .collect()
+}
+
+/// Find, for each test, the other tests whose coverage contains all of its covered lines
+///
+/// Tests without any coverage are skipped, since every test trivially contains them.
+pub fn find_subsuming_tests(
+    results: &HashMap<String, HashMap<String, HashSet<u64>>>,
+) -> HashMap<String, Vec<SubsumingTest>> {
+    let test_lines: HashMap<&String, HashSet<(&str, u64)>> = results
+        .iter()
+        .map(|(test_name, file_lines)| (test_name, flatten_lines(file_lines)))
+        .filter(|(_, lines)| !lines.is_empty())
+        .collect();
+
+    let mut subsumptions = HashMap::new();
+
+    for (test_name, lines) in &test_lines {
+        let mut subsumed_by: Vec<SubsumingTest> = test_lines
+            .iter()
+            .filter(|(other_test, other_lines)| {
+                other_test != &test_name
+                    && other_lines.len() >= lines.len()
+                    && lines.is_subset(other_lines)
+            })
+            .map(|(other_test, other_lines)| SubsumingTest {
+                test: other_test.to_string(),
+                equal: other_lines.len() == lines.len(),
+            })
+            .collect();
+
+        if !subsumed_by.is_empty() {
+            subsumed_by.sort_by(|a, b| a.test.cmp(&b.test));
+            subsumptions.insert(test_name.to_string(), subsumed_by);
+        }
+    }
+
+    subsumptions
+}
@@ -0,0 +1,159 @@
+use crate::coverage::tarpaulin::list_binary_test_ids;
+use crate::types::errors::Error;
+use crate::types::models::TestCoverage;
+use crate::utils::metadata::PackageRoots;
+use crate::utils::paths::test_cache_path;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Version of the cache entry format and of how the coverage in it is extracted
+///
+/// Bump this whenever a change to how tests are run or their reports are read would make
+/// coverage cached by older builds wrong.
+pub const CACHE_VERSION: u32 = 1;
+
+/// A test's extracted coverage, with the inputs it was produced from
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CacheEntry {
+    /// Entry format version, 0 for entries written before versioning was introduced
+    #[serde(default)]
+    pub version: u32,
+    /// Hash of the test executable containing the test
+    pub binary_hash: String,
+    /// Hash of each source file tarpaulin instrumented, keyed by path
+    pub source_hashes: BTreeMap<String, String>,
+    /// Crates whose files were kept from the report
+    #[serde(default)]
+    pub crates: Vec<String>,
+    pub coverage: TestCoverage,
+}
+
+/// Per-test coverage cached in the output directory across runs
+///
+/// An entry is reused only if it was written by a build with the same cache version, the test
+/// executable containing the test and the contents of every source file in its report are
+/// unchanged, and the same crates are reported.
+pub struct CoverageCache {
+    output_dir: PathBuf,
+    /// Hash of the test executable containing each test, keyed by qualified test name
+    binary_hashes: HashMap<String, String>,
+    /// Hash of each source file read so far, `None` if it could not be read
+    file_hashes: HashMap<String, Option<String>>,
+    /// Directory that the files in coverage are relative to
+    source_root: PathBuf,
+    /// Crates whose files are kept from reports, as entries must have been stored with
+    crates: Vec<String>,
+}
+
+/// Hash bytes with SHA-256, as lowercase hex
+fn hash_bytes(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+impl CoverageCache {
+    /// Create a cache from precomputed hashes of the test executable containing each test
+    pub fn new(output_dir: &Path, binary_hashes: HashMap<String, String>) -> Self {
+        Self {
+            output_dir: output_dir.to_path_buf(),
+            binary_hashes,
+            file_hashes: HashMap::new(),
+            source_root: PathBuf::new(),
+            crates: Vec::new(),
+        }
+    }
+
+    /// Open the cache for the packages, hashing their test executables
+    /// This function assumes the packages' tests have already been built
+    pub fn open(
+        packages: &[String],
+        roots: &PackageRoots,
+        output_dir: &Path,
+    ) -> Result<Self, Error> {
+        let mut binary_hashes = HashMap::new();
+        let mut executable_hashes: HashMap<PathBuf, String> = HashMap::new();
+        for (test_id, binary) in list_binary_test_ids(packages)? {
+            let binary_hash = match executable_hashes.get(&binary.executable) {
+                Some(binary_hash) => binary_hash.clone(),
+                None => {
+                    let binary_hash = hash_bytes(&std::fs::read(&binary.executable)?);
+                    executable_hashes.insert(binary.executable.clone(), binary_hash.clone());
+                    binary_hash
+                }
+            };
+            binary_hashes.insert(test_id.to_string(), binary_hash);
+        }
+
+        Ok(Self {
+            source_root: roots.base().to_path_buf(),
+            crates: roots.reported_crates(),
+            ..Self::new(output_dir, binary_hashes)
+        })
+    }
+
+    fn file_hash(&mut self, file: &str) -> Option<String> {
+        self.file_hashes
+            .entry(file.to_string())
+            .or_insert_with(|| {
+                std::fs::read(self.source_root.join(file))
+                    .ok()
+                    .map(|bytes| hash_bytes(&bytes))
+            })
+            .clone()
+    }
+
+    /// Get a test's cached coverage, if its inputs have not changed since it was stored
+    pub fn lookup(&mut self, test_name: &str) -> Option<TestCoverage> {
+        let binary_hash = self.binary_hashes.get(test_name)?.clone();
+        let content = std::fs::read_to_string(test_cache_path(&self.output_dir, test_name)).ok()?;
+        let entry: CacheEntry = serde_json::from_str(&content).ok()?;
+
+        if entry.version != CACHE_VERSION
+            || entry.crates != self.crates
+            || entry.binary_hash != binary_hash
+        {
+            return None;
+        }
+        for (file, source_hash) in &entry.source_hashes {
+            if self.file_hash(file).as_ref() != Some(source_hash) {
+                return None;
+            }
+        }
+
+        Some(entry.coverage)
+    }
+
+    /// Store a test's freshly collected coverage
+    ///
+    /// Tests not found in any test executable are not cached, as their inputs are unknown.
+    pub fn store(&mut self, test_name: &str, coverage: &TestCoverage) -> Result<(), Error> {
+        let Some(binary_hash) = self.binary_hashes.get(test_name).cloned() else {
+            return Ok(());
+        };
+
+        let mut source_hashes = BTreeMap::new();
+        for file in coverage.coverable.keys() {
+            // A source that cannot be read cannot be checked later, so do not cache the test
+            let Some(source_hash) = self.file_hash(file) else {
+                return Ok(());
+            };
+            source_hashes.insert(file.clone(), source_hash);
+        }
+
+        let entry = CacheEntry {
+            version: CACHE_VERSION,
+            binary_hash,
+            source_hashes,
+            crates: self.crates.clone(),
+            coverage: coverage.clone(),
+        };
+
+        let cache_path = test_cache_path(&self.output_dir, test_name);
+        if let Some(parent) = cache_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&cache_path, serde_json::to_string(&entry)?)?;
+        Ok(())
+    }
+}
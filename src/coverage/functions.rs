@@ -0,0 +1,230 @@
+use crate::types::models::{FunctionCoverage, FunctionSummary};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+
+/// Lines spanned by a function or method in a source file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionSpan {
+    /// Name qualified by its enclosing inline modules, impl or trait within the file, followed
+    /// by its start line if several functions in the file share the name
+    /// Example: "tests::test_foo", "Parser::parse_expr", "<Foo as Display>::fmt",
+    /// "Wrapper::new (line 12)"
+    pub name: String,
+    pub start_line: u64,
+    pub end_line: u64,
+}
+
+/// Visitor collecting every function with a body, tracking the enclosing scope
+#[derive(Default)]
+struct FunctionCollector {
+    scope: Vec<String>,
+    spans: Vec<FunctionSpan>,
+}
+
+impl FunctionCollector {
+    fn qualified(&self, name: &str) -> String {
+        let mut parts = self.scope.clone();
+        parts.push(name.to_string());
+        parts.join("::")
+    }
+
+    fn record(&mut self, name: String, start: proc_macro2::Span, end: proc_macro2::Span) {
+        self.spans.push(FunctionSpan {
+            name,
+            start_line: start.start().line as u64,
+            end_line: end.end().line as u64,
+        });
+    }
+}
+
+/// Name an impl's self type by its last path segment, looking through references
+fn type_name(ty: &syn::Type) -> String {
+    match ty {
+        syn::Type::Path(type_path) => type_path
+            .path
+            .segments
+            .last()
+            .map(|segment| segment.ident.to_string())
+            .unwrap_or_default(),
+        syn::Type::Reference(reference) => type_name(&reference.elem),
+        syn::Type::Paren(paren) => type_name(&paren.elem),
+        syn::Type::Group(group) => type_name(&group.elem),
+        _ => "_".to_string(),
+    }
+}
+
+impl<'ast> Visit<'ast> for FunctionCollector {
+    fn visit_item_mod(&mut self, item: &'ast syn::ItemMod) {
+        self.scope.push(item.ident.to_string());
+        visit::visit_item_mod(self, item);
+        self.scope.pop();
+    }
+
+    fn visit_item_impl(&mut self, item: &'ast syn::ItemImpl) {
+        let self_ty = type_name(&item.self_ty);
+        let scope = match &item.trait_ {
+            Some((_, trait_path, _)) => {
+                let trait_name = trait_path
+                    .segments
+                    .last()
+                    .map(|segment| segment.ident.to_string())
+                    .unwrap_or_default();
+                format!("<{} as {}>", self_ty, trait_name)
+            }
+            None => self_ty,
+        };
+        self.scope.push(scope);
+        visit::visit_item_impl(self, item);
+        self.scope.pop();
+    }
+
+    fn visit_item_trait(&mut self, item: &'ast syn::ItemTrait) {
+        self.scope.push(item.ident.to_string());
+        visit::visit_item_trait(self, item);
+        self.scope.pop();
+    }
+
+    fn visit_item_fn(&mut self, item: &'ast syn::ItemFn) {
+        let name = self.qualified(&item.sig.ident.to_string());
+        self.record(name, item.sig.span(), item.block.span());
+        self.scope.push(item.sig.ident.to_string());
+        visit::visit_item_fn(self, item);
+        self.scope.pop();
+    }
+
+    fn visit_impl_item_fn(&mut self, item: &'ast syn::ImplItemFn) {
+        let name = self.qualified(&item.sig.ident.to_string());
+        self.record(name, item.sig.span(), item.block.span());
+        self.scope.push(item.sig.ident.to_string());
+        visit::visit_impl_item_fn(self, item);
+        self.scope.pop();
+    }
+
+    fn visit_trait_item_fn(&mut self, item: &'ast syn::TraitItemFn) {
+        // Only default methods have a body whose lines can be covered
+        if let Some(block) = &item.default {
+            let name = self.qualified(&item.sig.ident.to_string());
+            self.record(name, item.sig.span(), block.span());
+        }
+        self.scope.push(item.sig.ident.to_string());
+        visit::visit_trait_item_fn(self, item);
+        self.scope.pop();
+    }
+}
+
+/// Parse a Rust source file and find the line span of every function and method in it
+pub fn find_function_spans(source: &str) -> Result<Vec<FunctionSpan>, syn::Error> {
+    let file = syn::parse_file(source)?;
+    let mut collector = FunctionCollector::default();
+    collector.visit_file(&file);
+
+    // Functions are keyed by name, so tell apart e.g. methods of `impl Foo<A>` and `impl Foo<B>`
+    let mut name_counts: HashMap<String, usize> = HashMap::new();
+    for span in &collector.spans {
+        *name_counts.entry(span.name.clone()).or_default() += 1;
+    }
+    for span in &mut collector.spans {
+        if name_counts[&span.name] > 1 {
+            span.name = format!("{} (line {})", span.name, span.start_line);
+        }
+    }
+    Ok(collector.spans)
+}
+
+/// Find the innermost function containing a line, if any
+pub fn enclosing_function(spans: &[FunctionSpan], line: u64) -> Option<&FunctionSpan> {
+    spans
+        .iter()
+        .filter(|span| span.start_line <= line && line <= span.end_line)
+        .min_by_key(|span| span.end_line - span.start_line)
+}
+
+/// Parse each file's source and find its function spans, skipping files that fail to parse
+pub fn map_functions(sources: &HashMap<String, String>) -> HashMap<String, Vec<FunctionSpan>> {
+    let mut functions = HashMap::new();
+
+    for (file, source) in sources {
+        match find_function_spans(source) {
+            Ok(spans) => {
+                functions.insert(file.clone(), spans);
+            }
+            Err(e) => {
+                eprintln!(
+                    "Warning: Failed to parse '{}' for function attribution: {}",
+                    file, e
+                );
+            }
+        }
+    }
+
+    functions
+}
+
+/// Group a file's covered lines by the function that encloses them
+pub fn lines_by_function<'a>(
+    spans: &'a [FunctionSpan],
+    lines: &HashSet<u64>,
+) -> HashMap<&'a str, HashSet<u64>> {
+    let mut grouped: HashMap<&str, HashSet<u64>> = HashMap::new();
+    for line in lines {
+        if let Some(span) = enclosing_function(spans, *line) {
+            grouped.entry(span.name.as_str()).or_default().insert(*line);
+        }
+    }
+    grouped
+}
+
+/// Attribute a test's covered lines in a file to functions, marking the ones no other test reaches
+pub fn attribute_functions(
+    spans: &[FunctionSpan],
+    lines: &HashSet<u64>,
+    unique_lines: &HashSet<u64>,
+    function_tests: &HashMap<&str, Vec<String>>,
+) -> BTreeMap<String, FunctionCoverage> {
+    lines_by_function(spans, lines)
+        .into_iter()
+        .map(|(name, covered)| {
+            let coverage = FunctionCoverage {
+                covered_lines: covered.len() as u32,
+                unique_covered_lines: covered.intersection(unique_lines).count() as u32,
+                only_covering_test: function_tests
+                    .get(name)
+                    .is_some_and(|tests| tests.len() == 1),
+            };
+            (name.to_string(), coverage)
+        })
+        .collect()
+}
+
+/// Summarize every function in a file: its span, coverable and covered lines, and covering tests
+pub fn summarize_functions(
+    spans: &[FunctionSpan],
+    coverable: Option<&HashSet<u64>>,
+    covered: &HashSet<u64>,
+    function_tests: &HashMap<&str, Vec<String>>,
+) -> BTreeMap<String, FunctionSummary> {
+    let coverable_by_function = coverable
+        .map(|coverable| lines_by_function(spans, coverable))
+        .unwrap_or_default();
+    let covered_by_function = lines_by_function(spans, covered);
+
+    spans
+        .iter()
+        .filter(|span| {
+            coverable_by_function.contains_key(span.name.as_str())
+                || covered_by_function.contains_key(span.name.as_str())
+        })
+        .map(|span| {
+            let name = span.name.as_str();
+            let summary = FunctionSummary {
+                start_line: span.start_line,
+                end_line: span.end_line,
+                coverable_lines: coverable_by_function.get(name).map_or(0, HashSet::len) as u32,
+                covered_lines: covered_by_function.get(name).map_or(0, HashSet::len) as u32,
+                tests: function_tests.get(name).cloned().unwrap_or_default(),
+            };
+            (span.name.clone(), summary)
+        })
+        .collect()
+}
@@ -0,0 +1,90 @@
+use crate::types::errors::Error;
+use crate::types::models::{CollectedCoverage, PartialCoverage};
+use std::collections::BTreeSet;
+
+/// Combine the raw coverage of several shards of the same package into one
+///
+/// Every shard of the run must be present exactly once, since uniqueness can only be
+/// computed over the full set of tests, and all shards must come from the same selection of
+/// tests with the same doctest setting.
+pub fn merge_partials(parts: Vec<PartialCoverage>) -> Result<CollectedCoverage, Error> {
+    let Some(first) = parts.first() else {
+        return Err(Error::MergeFailed(
+            "no partial coverage to merge".to_string(),
+        ));
+    };
+    let package = first.package.clone();
+    let shard_count = first.shard.count;
+
+    let mut seen_shards = BTreeSet::new();
+    for part in &parts {
+        if part.package != package {
+            return Err(Error::MergeFailed(format!(
+                "shards are from different packages ('{}' and '{}')",
+                package, part.package
+            )));
+        }
+        if part.shard.count != shard_count {
+            return Err(Error::MergeFailed(format!(
+                "shard {} does not belong to a run split into {} shards",
+                part.shard, shard_count
+            )));
+        }
+        if part.tests != first.tests {
+            return Err(Error::MergeFailed(format!(
+                "shard {} was run with a different selection of tests",
+                part.shard
+            )));
+        }
+        if part.doctests != first.doctests {
+            return Err(Error::MergeFailed(format!(
+                "shard {} was run {} --doctests, unlike shard {}",
+                part.shard,
+                if part.doctests { "with" } else { "without" },
+                first.shard
+            )));
+        }
+        if !seen_shards.insert(part.shard.index) {
+            return Err(Error::MergeFailed(format!(
+                "shard {} was given more than once",
+                part.shard
+            )));
+        }
+    }
+
+    let missing: Vec<String> = (1..=shard_count)
+        .filter(|index| !seen_shards.contains(index))
+        .map(|index| format!("{}/{}", index, shard_count))
+        .collect();
+    if !missing.is_empty() {
+        return Err(Error::MergeFailed(format!(
+            "missing shards {}",
+            missing.join(", ")
+        )));
+    }
+
+    let mut merged = CollectedCoverage::default();
+    for part in parts {
+        let coverage = part.coverage;
+        for (file, lines) in coverage.coverable {
+            merged.coverable.entry(file).or_default().extend(lines);
+        }
+        for (test_name, files) in coverage.tests {
+            if merged.tests.contains_key(&test_name) {
+                return Err(Error::MergeFailed(format!(
+                    "test '{}' appears in more than one shard",
+                    test_name
+                )));
+            }
+            merged.tests.insert(test_name, files);
+        }
+        merged.hits.extend(coverage.hits);
+        // Sources are the same in every shard built from the same revision
+        for (file, source) in coverage.sources {
+            merged.sources.entry(file).or_insert(source);
+        }
+        merged.crates.extend(coverage.crates);
+    }
+
+    Ok(merged)
+}
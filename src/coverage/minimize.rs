@@ -0,0 +1,95 @@
+use crate::coverage::analysis::flatten_lines;
+use crate::types::models::{KeptTest, SuiteMinimization};
+use std::collections::{HashMap, HashSet};
+
+/// Select a minimal subset of tests that covers every line the full suite covers
+///
+/// Tests are chosen greedily by how many not-yet-covered lines they add (ties broken by
+/// name for deterministic output), then any kept test made redundant by later picks is
+/// dropped again.
+pub fn minimize_test_suite(
+    results: &HashMap<String, HashMap<String, HashSet<u64>>>,
+) -> SuiteMinimization {
+    // Flatten each test's coverage into a set of (file, line) pairs
+    let test_lines: HashMap<&str, HashSet<(&str, u64)>> = results
+        .iter()
+        .map(|(test_name, file_lines)| (test_name.as_str(), flatten_lines(file_lines)))
+        .collect();
+
+    let all_lines: HashSet<(&str, u64)> = test_lines.values().flatten().copied().collect();
+
+    // Sort candidates by name so that ties are always resolved the same way
+    let mut candidates: Vec<&str> = test_lines.keys().copied().collect();
+    candidates.sort();
+
+    let mut uncovered = all_lines.clone();
+    let mut picked: Vec<&str> = Vec::new();
+
+    while !uncovered.is_empty() {
+        let best = candidates
+            .iter()
+            .filter(|test| !picked.contains(test))
+            .map(|test| (*test, test_lines[test].intersection(&uncovered).count()))
+            .fold(None, |best: Option<(&str, usize)>, candidate| match best {
+                Some((_, best_count)) if best_count >= candidate.1 => best,
+                _ => Some(candidate),
+            });
+
+        match best {
+            Some((test, gain)) if gain > 0 => {
+                for line in &test_lines[test] {
+                    uncovered.remove(line);
+                }
+                picked.push(test);
+            }
+            _ => break,
+        }
+    }
+
+    // Drop any picked test whose lines are all covered by the other picked tests,
+    // checking the least valuable (latest picked) tests first
+    let mut line_counts: HashMap<(&str, u64), u32> = HashMap::new();
+    for test in &picked {
+        for line in &test_lines[test] {
+            *line_counts.entry(*line).or_default() += 1;
+        }
+    }
+    for idx in (0..picked.len()).rev() {
+        let test = picked[idx];
+        let redundant = test_lines[test].iter().all(|line| line_counts[line] > 1);
+        if redundant {
+            for line in &test_lines[test] {
+                if let Some(count) = line_counts.get_mut(line) {
+                    *count -= 1;
+                }
+            }
+            picked.remove(idx);
+        }
+    }
+
+    // Record how many new lines each kept test contributes in selection order
+    let mut seen: HashSet<(&str, u64)> = HashSet::new();
+    let kept: Vec<KeptTest> = picked
+        .iter()
+        .map(|test| {
+            let before = seen.len();
+            seen.extend(test_lines[test].iter().copied());
+            KeptTest {
+                name: test.to_string(),
+                new_covered_lines: (seen.len() - before) as u32,
+            }
+        })
+        .collect();
+
+    let droppable: Vec<String> = candidates
+        .iter()
+        .filter(|test| !picked.contains(test))
+        .map(|test| test.to_string())
+        .collect();
+
+    SuiteMinimization {
+        total_covered_lines: all_lines.len() as u32,
+        kept,
+        droppable,
+    }
+}
@@ -0,0 +1,197 @@
+use crate::types::errors::Error;
+use crate::types::models::TestCoverage;
+use crate::utils::paths::progress_manifest_path;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Parameters a run's results depend on, written as the first line of the manifest
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ProgressHeader {
+    /// Crates whose files are kept from the reports
+    pub package: String,
+    /// Version of isotarp that extracted the coverage
+    pub version: String,
+    /// Whether doctests were selected
+    #[serde(default)]
+    pub doctests: bool,
+    /// Tests selected for the run, sorted
+    #[serde(default)]
+    pub tests: Vec<String>,
+}
+
+impl ProgressHeader {
+    pub fn new(package_name: &str, doctests: bool, test_names: &[String]) -> Self {
+        let mut tests = test_names.to_vec();
+        tests.sort();
+        Self {
+            package: package_name.to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            doctests,
+            tests,
+        }
+    }
+
+    /// Describe how the parameters of another run differ from these
+    fn differences(&self, other: &ProgressHeader) -> Vec<String> {
+        let mut differences = Vec::new();
+        if self.package != other.package {
+            differences.push(format!(
+                "packages '{}' instead of '{}'",
+                other.package, self.package
+            ));
+        }
+        if self.version != other.version {
+            differences.push(format!(
+                "isotarp {} instead of {}",
+                other.version, self.version
+            ));
+        }
+        if self.doctests != other.doctests {
+            differences.push(format!(
+                "--doctests {}",
+                if other.doctests { "given" } else { "not given" }
+            ));
+        }
+        if self.tests != other.tests {
+            differences.push(format!(
+                "{} tests selected instead of {}",
+                other.tests.len(),
+                self.tests.len()
+            ));
+        }
+        differences
+    }
+}
+
+/// A test whose coverage was collected before the run finished
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CompletedTest {
+    pub test: String,
+    pub coverage: TestCoverage,
+}
+
+/// Manifest of the tests completed so far in a run, kept in the output directory
+///
+/// The manifest is a JSON Lines file: a header line with the run's parameters, then one line
+/// per completed test, appended as soon as it finishes. A run that dies part way through
+/// loses at most the line being written, which is skipped when the manifest is read back.
+pub struct ProgressManifest {
+    file: Mutex<File>,
+}
+
+impl ProgressManifest {
+    /// Start a new manifest, discarding any left by an earlier run
+    pub fn create(output_dir: &Path, header: &ProgressHeader) -> Result<Self, Error> {
+        let mut file = File::create(progress_manifest_path(output_dir))?;
+        writeln!(file, "{}", serde_json::to_string(header)?)?;
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+
+    /// Continue the manifest left by an earlier run, returning the tests it completed
+    ///
+    /// If there is no manifest a new one is started. A manifest written for other parameters
+    /// is an error, as its coverage would not match this run's.
+    pub fn resume(
+        output_dir: &Path,
+        header: &ProgressHeader,
+    ) -> Result<(Self, HashMap<String, TestCoverage>), Error> {
+        let path = progress_manifest_path(output_dir);
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            return Ok((Self::create(output_dir, header)?, HashMap::new()));
+        };
+
+        let mut lines = content.lines();
+        let earlier = lines
+            .next()
+            .and_then(|line| serde_json::from_str::<ProgressHeader>(line).ok());
+        let Some(earlier) = earlier else {
+            return Err(Error::ResumeFailed(format!(
+                "progress manifest '{}' has no readable header",
+                path.display()
+            )));
+        };
+        let differences = earlier.differences(header);
+        if !differences.is_empty() {
+            return Err(Error::ResumeFailed(format!(
+                "progress manifest '{}' is from a run with other parameters ({}); rerun without --resume to start over",
+                path.display(),
+                differences.join(", ")
+            )));
+        }
+
+        // A line cut short by the interruption fails to parse, and its test is simply rerun
+        let completed: HashMap<String, TestCoverage> = lines
+            .filter_map(|line| serde_json::from_str::<CompletedTest>(line).ok())
+            .map(|completed| (completed.test, completed.coverage))
+            .collect();
+
+        // Keep appending to the manifest, ending any line cut short so new entries parse
+        let mut file = OpenOptions::new().append(true).open(&path)?;
+        if !content.is_empty() && !content.ends_with('\n') {
+            writeln!(file)?;
+        }
+        let manifest = Self {
+            file: Mutex::new(file),
+        };
+
+        Ok((manifest, completed))
+    }
+
+    /// Append a completed test to the manifest
+    pub fn record(&self, test_name: &str, coverage: &TestCoverage) -> Result<(), Error> {
+        let line = serde_json::to_string(&CompletedTest {
+            test: test_name.to_string(),
+            coverage: coverage.clone(),
+        })?;
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        writeln!(file, "{}", line)?;
+        file.flush()?;
+        Ok(())
+    }
+}
+
+/// Count the tests completed by the run that left a manifest in the output directory
+fn count_completed(output_dir: &Path) -> usize {
+    std::fs::read_to_string(progress_manifest_path(output_dir))
+        .map(|content| {
+            content
+                .lines()
+                .skip(1)
+                .filter(|line| serde_json::from_str::<CompletedTest>(line).is_ok())
+                .count()
+        })
+        .unwrap_or_default()
+}
+
+/// Open the progress manifest for a run, resuming an earlier one if requested
+///
+/// Starting over discards the progress of any earlier run in the output directory, with a
+/// warning if it had completed tests.
+pub fn open_progress(
+    output_dir: &Path,
+    header: &ProgressHeader,
+    resume: bool,
+) -> Result<(ProgressManifest, HashMap<String, TestCoverage>), Error> {
+    if resume {
+        return ProgressManifest::resume(output_dir, header);
+    }
+
+    let discarded = count_completed(output_dir);
+    if discarded > 0 {
+        eprintln!(
+            "Warning: Discarding the progress of an earlier run in '{}' ({} tests completed); use 'analyze --resume' to continue it instead",
+            output_dir.display(),
+            discarded
+        );
+    }
+    Ok((
+        ProgressManifest::create(output_dir, header)?,
+        HashMap::new(),
+    ))
+}
@@ -0,0 +1,101 @@
+use crate::coverage::analysis::flatten_lines;
+use crate::types::models::{SimilarityMetric, TestSimilarity};
+use std::collections::{HashMap, HashSet};
+
+/// Find the root of a test's cluster, compressing the path along the way
+fn find_root(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+/// Compute Jaccard and overlap similarity between every pair of tests' covered lines,
+/// and cluster tests whose chosen similarity reaches the threshold
+///
+/// Clusters are single-linkage: two tests share a cluster if a chain of pairs at or above
+/// the threshold connects them. Tests with no similar partner are not reported as clusters.
+pub fn compute_test_similarity(
+    results: &HashMap<String, HashMap<String, HashSet<u64>>>,
+    metric: SimilarityMetric,
+    threshold: f64,
+) -> TestSimilarity {
+    let mut tests: Vec<String> = results.keys().cloned().collect();
+    tests.sort();
+
+    let lines: Vec<HashSet<(&str, u64)>> = tests
+        .iter()
+        .map(|test_name| flatten_lines(&results[test_name]))
+        .collect();
+
+    let n = tests.len();
+    let mut jaccard = vec![vec![0.0; n]; n];
+    let mut overlap = vec![vec![0.0; n]; n];
+
+    for i in 0..n {
+        jaccard[i][i] = 1.0;
+        overlap[i][i] = 1.0;
+        for j in (i + 1)..n {
+            let shared = lines[i].intersection(&lines[j]).count();
+            let union = lines[i].len() + lines[j].len() - shared;
+            let smaller = lines[i].len().min(lines[j].len());
+
+            // Tests without coverage are not similar to anything
+            let jaccard_ij = if union > 0 {
+                shared as f64 / union as f64
+            } else {
+                0.0
+            };
+            let overlap_ij = if smaller > 0 {
+                shared as f64 / smaller as f64
+            } else {
+                0.0
+            };
+
+            jaccard[i][j] = jaccard_ij;
+            jaccard[j][i] = jaccard_ij;
+            overlap[i][j] = overlap_ij;
+            overlap[j][i] = overlap_ij;
+        }
+    }
+
+    let matrix = match metric {
+        SimilarityMetric::Jaccard => &jaccard,
+        SimilarityMetric::Overlap => &overlap,
+    };
+
+    // Union-find over the pairs that reach the threshold
+    let mut parent: Vec<usize> = (0..n).collect();
+    for (i, row) in matrix.iter().enumerate() {
+        for (j, similarity) in row.iter().enumerate().skip(i + 1) {
+            if *similarity >= threshold && *similarity > 0.0 {
+                let (root_i, root_j) = (find_root(&mut parent, i), find_root(&mut parent, j));
+                if root_i != root_j {
+                    parent[root_j.max(root_i)] = root_i.min(root_j);
+                }
+            }
+        }
+    }
+
+    // Group tests by their root, keeping name order within and between clusters
+    let mut groups: HashMap<usize, Vec<String>> = HashMap::new();
+    for (i, test_name) in tests.iter().enumerate() {
+        let root = find_root(&mut parent, i);
+        groups.entry(root).or_default().push(test_name.clone());
+    }
+    let mut clusters: Vec<Vec<String>> = groups
+        .into_values()
+        .filter(|members| members.len() > 1)
+        .collect();
+    clusters.sort();
+
+    TestSimilarity {
+        tests,
+        jaccard,
+        overlap,
+        metric,
+        threshold,
+        clusters,
+    }
+}
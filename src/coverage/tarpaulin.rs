@@ -1,19 +1,25 @@
 use crate::types::errors::Error;
-use crate::types::models::{LineStat, TarpaulinReport};
+use crate::types::models::{
+    LineHits, LineStat, SourceFile, TargetKind, TarpaulinReport, TestBinary, TestCoverage, TestId,
+    TestTarget,
+};
+use crate::utils::metadata::PackageRoots;
 use crate::utils::paths::{test_output_dir, test_report_path};
 use std::collections::{HashMap, HashSet};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
-/// Run a specific test using tarpaulin and return the covered lines
-/// This function assumes the package has already been built
+/// Run a specific test using tarpaulin and return the covered and coverable lines of the
+/// given packages
+/// This function assumes the packages have already been built
 pub fn run_isolated_test_coverage(
-    package_name: &str,
+    packages: &[String],
+    roots: &PackageRoots,
     test_name: &str,
     output_dir: &Path,
     target_dir: &Path,
     skip_clean: bool,
-) -> Result<HashMap<String, HashSet<u64>>, Error> {
+) -> Result<TestCoverage, Error> {
     // Create output directory for this test
     let test_output_dir = test_output_dir(output_dir, test_name);
     std::fs::create_dir_all(&test_output_dir).map_err(|e| {
@@ -27,26 +33,43 @@ pub fn run_isolated_test_coverage(
         ))
     })?;
 
+    // Select the test's own target if it is qualified, so tests with the same path in other
+    // targets are not run too
+    let (test_packages, target_args, test_filter) = match test_name.parse::<TestId>() {
+        Ok(test_id) => (
+            test_id.package.map(|package| vec![package]),
+            test_id.target.cargo_args(),
+            test_id.path,
+        ),
+        Err(_) => (None, Vec::new(), test_name.to_string()),
+    };
+
     // Build command arguments
-    let args = vec![
-        "tarpaulin",
-        "-p",
-        package_name,
-        "--no-fail-fast",
+    let mut args = vec!["tarpaulin".to_string()];
+    for package in test_packages.as_deref().unwrap_or(packages) {
+        args.extend(["-p".to_string(), package.clone()]);
+    }
+    args.extend([
+        "--no-fail-fast".to_string(),
         if skip_clean {
-            "--skip-clean"
+            "--skip-clean".to_string()
         } else {
-            "--force-clean"
+            "--force-clean".to_string()
         },
-        "--target-dir",
-        target_dir.to_str().unwrap_or_default(),
-        "-o",
-        "Json",
-        "--output-dir",
-        test_output_dir.to_str().unwrap_or_default(),
-        "--",
-        test_name,
-    ];
+        "--target-dir".to_string(),
+        target_dir.to_string_lossy().to_string(),
+        "-o".to_string(),
+        "Json".to_string(),
+        "--output-dir".to_string(),
+        test_output_dir.to_string_lossy().to_string(),
+    ]);
+    args.extend(target_args);
+    args.extend([
+        "--".to_string(),
+        test_filter,
+        // libtest filters by substring, so without this `test_foo` would also run `test_foo_bar`
+        "--exact".to_string(),
+    ]);
 
     // Run tarpaulin for this specific test
     println!("Running coverage for test: {}", test_name);
@@ -78,25 +101,101 @@ pub fn run_isolated_test_coverage(
 
     let report: TarpaulinReport = serde_json::from_str(&report_content).map_err(Error::Json)?;
 
-    // Extract the covered lines
-    let covered_lines = extract_covered_lines(&report, package_name);
+    Ok(extract_test_coverage(&report, roots))
+}
 
-    Ok(covered_lines)
+/// Name a file in a tarpaulin report, if it belongs to one of the packages being analyzed
+fn package_path(file: &SourceFile, roots: &PackageRoots) -> Option<String> {
+    let path: PathBuf = file.path.iter().collect();
+    roots.relative_path(&path)
+}
+
+/// Split a path into components as tarpaulin writes them, e.g. `["/", "ws", "src", "lib.rs"]`
+fn path_components(path: &Path) -> Vec<String> {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy().to_string())
+        .collect()
+}
+
+/// Move the files of a report produced in a checkout elsewhere, e.g. on CI, to the local one
+///
+/// Every directory that, with a report file's path below it, names a source file of the
+/// analyzed packages on disk is a candidate for the report's checkout. The candidate under
+/// which the most report files exist locally is chosen, so a workspace member's file cannot
+/// pass for a file of the root package, and every file under it is moved under the local
+/// workspace root. Reports produced locally are left as they are.
+pub fn reroot_report(report: &mut TarpaulinReport, roots: &PackageRoots) {
+    let paths: Vec<PathBuf> = report
+        .files
+        .iter()
+        .map(|file| file.path.iter().collect())
+        .collect();
+    if paths.iter().any(|path| roots.relative_path(path).is_some()) {
+        return;
+    }
+
+    let mut candidates: Vec<PathBuf> = paths
+        .iter()
+        .flat_map(|path| {
+            let components: Vec<_> = path.components().collect();
+            (1..components.len())
+                .filter(|start| {
+                    let suffix: PathBuf = components[*start..].iter().collect();
+                    let local = roots.base().join(&suffix);
+                    local.is_file() && roots.relative_path(&local).is_some()
+                })
+                .map(|start| components[..start].iter().collect::<PathBuf>())
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    candidates.sort();
+    candidates.dedup();
+
+    let rerooted = |checkout: &Path| {
+        paths
+            .iter()
+            .filter_map(|path| path.strip_prefix(checkout).ok())
+            .filter(|relative| roots.base().join(relative).is_file())
+            .count()
+    };
+    // Ties go to the shallowest checkout, which holds every file of a deeper one
+    let Some(checkout) = candidates.into_iter().max_by_key(|checkout| {
+        (
+            rerooted(checkout),
+            std::cmp::Reverse(checkout.components().count()),
+        )
+    }) else {
+        return;
+    };
+
+    for (file, path) in report.files.iter_mut().zip(paths) {
+        if let Ok(relative) = path.strip_prefix(&checkout) {
+            file.path = path_components(&roots.base().join(relative));
+        }
+    }
+}
+
+/// Extract both the covered and the coverable lines from a tarpaulin report
+pub fn extract_test_coverage(report: &TarpaulinReport, roots: &PackageRoots) -> TestCoverage {
+    TestCoverage {
+        covered: extract_covered_lines(report, roots),
+        coverable: extract_coverable_lines(report, roots),
+        hits: extract_line_hits(report, roots),
+    }
 }
 
 /// Extract covered lines from a tarpaulin report
 pub fn extract_covered_lines(
     report: &TarpaulinReport,
-    package_name: &str,
+    roots: &PackageRoots,
 ) -> HashMap<String, HashSet<u64>> {
     let mut covered_lines = HashMap::new();
 
     for file in &report.files {
-        // Extract path parts to see if this file belongs to the package we're analyzing
-        let path_str = file.path.join("/");
-        if !path_str.contains(package_name) {
+        // Skip files outside the packages we're analyzing
+        let Some(path_str) = package_path(file, roots) else {
             continue;
-        }
+        };
 
         // Get covered lines for this file
         let lines: HashSet<u64> = file
@@ -117,31 +216,235 @@ pub fn extract_covered_lines(
     covered_lines
 }
 
-/// Get all test names from the package
-pub fn list_tests(package_name: &str) -> Result<Vec<String>, Error> {
+/// Extract coverable lines from a tarpaulin report, including those with zero hits
+pub fn extract_coverable_lines(
+    report: &TarpaulinReport,
+    roots: &PackageRoots,
+) -> HashMap<String, HashSet<u64>> {
+    let mut coverable_lines = HashMap::new();
+
+    for file in &report.files {
+        // Skip files outside the packages we're analyzing
+        let Some(path_str) = package_path(file, roots) else {
+            continue;
+        };
+
+        // Every trace is a line tarpaulin could instrument, whether or not it was hit
+        let lines: HashSet<u64> = file.traces.iter().map(|trace| trace.line).collect();
+
+        if !lines.is_empty() {
+            coverable_lines.insert(path_str, lines);
+        }
+    }
+
+    coverable_lines
+}
+
+/// Extract the hit count of each covered line from a tarpaulin report
+pub fn extract_line_hits(report: &TarpaulinReport, roots: &PackageRoots) -> LineHits {
+    let mut line_hits = HashMap::new();
+
+    for file in &report.files {
+        // Skip files outside the packages we're analyzing
+        let Some(path_str) = package_path(file, roots) else {
+            continue;
+        };
+
+        // A line may have several traces (one per address), each counting the same executions
+        let mut hits: HashMap<u64, u64> = HashMap::new();
+        for trace in &file.traces {
+            let LineStat::Line(count) = trace.stats;
+            if count > 0 {
+                let entry = hits.entry(trace.line).or_default();
+                *entry = (*entry).max(count);
+            }
+        }
+
+        if !hits.is_empty() {
+            line_hits.insert(path_str, hits);
+        }
+    }
+
+    line_hits
+}
+
+/// Extract the source code of each file in the packages from a tarpaulin report
+pub fn extract_sources(report: &TarpaulinReport, roots: &PackageRoots) -> HashMap<String, String> {
+    report
+        .files
+        .iter()
+        .filter_map(|file| Some((package_path(file, roots)?, file.content.clone())))
+        .collect()
+}
+
+/// Get every test compiled into the packages' test executables, with the executable it is in
+/// Tests are qualified by their package only if there are several packages
+pub fn list_binary_test_ids(packages: &[String]) -> Result<Vec<(TestId, TestBinary)>, Error> {
+    let mut tests = Vec::new();
+    for package_name in packages {
+        for binary in list_test_binaries(package_name)? {
+            for path in list_binary_tests(&binary.executable)? {
+                let test_id = TestId {
+                    package: (packages.len() > 1).then(|| package_name.clone()),
+                    target: binary.target.clone(),
+                    path,
+                };
+                tests.push((test_id, binary.clone()));
+            }
+        }
+    }
+    Ok(tests)
+}
+
+/// Get all tests in the packages, qualified by the target each is compiled into
+/// Doctests are only listed if requested, as running them requires rustdoc
+pub fn list_tests(packages: &[String], include_doctests: bool) -> Result<Vec<String>, Error> {
+    let binary_tests = list_binary_test_ids(packages)?;
+    let mut tests: Vec<String> = binary_tests
+        .iter()
+        .map(|(test_id, _)| test_id.to_string())
+        .collect();
+
+    if include_doctests {
+        for package_name in packages {
+            // Only a library has doctests, listed under its name
+            let library = list_test_binaries(package_name)?
+                .into_iter()
+                .find(|binary| binary.target.kind == TargetKind::Lib);
+            let Some(library) = library else {
+                continue;
+            };
+            for path in list_doctests(package_name)? {
+                let test_id = TestId {
+                    package: (packages.len() > 1).then(|| package_name.clone()),
+                    target: TestTarget {
+                        kind: TargetKind::Doc,
+                        name: library.target.name.clone(),
+                    },
+                    path,
+                };
+                tests.push(test_id.to_string());
+            }
+        }
+    }
+
+    Ok(tests)
+}
+
+/// Get the names of the package's doctests
+/// Example: "src/lib.rs - foo (line 12)"
+pub fn list_doctests(package_name: &str) -> Result<Vec<String>, Error> {
     let output = Command::new("cargo")
-        .args(["test", "-p", package_name, "--", "--quiet", "--list"])
+        .args(["test", "-p", package_name, "--doc", "--", "--list"])
         .output()
         .map_err(|e| {
-            Error::CommandFailed(format!("Failed to execute 'cargo test --list': {}", e))
+            Error::CommandFailed(format!(
+                "Failed to execute 'cargo test --doc --list': {}",
+                e
+            ))
         })?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
         return Err(Error::CommandFailed(format!(
-            "cargo test --list failed: {}\nStderr: {}",
+            "cargo test --doc --list failed: {}\nStderr: {}",
             output.status, stderr
         )));
     }
 
     let output_str = String::from_utf8(output.stdout).map_err(Error::Utf8)?;
 
-    // Parse the output to extract test names
-    let tests: Vec<String> = output_str
+    Ok(parse_test_list(&output_str))
+}
+
+/// Parse the test names from the output of a test harness run with `--list`
+pub fn parse_test_list(output: &str) -> Vec<String> {
+    output
         .lines()
         .filter(|line| line.contains(": test"))
         .map(|line| line.trim().trim_end_matches(": test").to_string())
-        .collect();
+        .collect()
+}
 
-    Ok(tests)
+/// Get the package's test executables and their targets, building them if needed
+pub fn list_test_binaries(package_name: &str) -> Result<Vec<TestBinary>, Error> {
+    let output = Command::new("cargo")
+        .args([
+            "test",
+            "-p",
+            package_name,
+            "--no-run",
+            "--message-format=json",
+        ])
+        .output()
+        .map_err(|e| {
+            Error::CommandFailed(format!("Failed to execute 'cargo test --no-run': {}", e))
+        })?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::CommandFailed(format!(
+            "cargo test --no-run failed: {}\nStderr: {}",
+            output.status, stderr
+        )));
+    }
+
+    let output_str = String::from_utf8(output.stdout).map_err(Error::Utf8)?;
+
+    Ok(parse_test_binaries(&output_str))
+}
+
+/// Parse the test executables from cargo's JSON messages
+pub fn parse_test_binaries(output: &str) -> Vec<TestBinary> {
+    // Each line is a JSON message; test executables are artifacts built with the test profile
+    output
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .filter(|message| {
+            message["reason"] == "compiler-artifact" && message["profile"]["test"] == true
+        })
+        .filter_map(|message| {
+            let executable = message["executable"].as_str()?;
+            let kinds: Vec<&str> = message["target"]["kind"]
+                .as_array()?
+                .iter()
+                .filter_map(|kind| kind.as_str())
+                .collect();
+            Some(TestBinary {
+                target: TestTarget {
+                    kind: TargetKind::from_cargo_kinds(&kinds)?,
+                    name: message["target"]["name"].as_str()?.to_string(),
+                },
+                executable: PathBuf::from(executable),
+            })
+        })
+        .collect()
+}
+
+/// Get the names of the tests in a single test executable
+pub fn list_binary_tests(binary: &Path) -> Result<Vec<String>, Error> {
+    let output = Command::new(binary)
+        .args(["--list"])
+        .output()
+        .map_err(|e| {
+            Error::CommandFailed(format!(
+                "Failed to execute '{} --list': {}",
+                binary.display(),
+                e
+            ))
+        })?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::CommandFailed(format!(
+            "{} --list failed: {}\nStderr: {}",
+            binary.display(),
+            output.status,
+            stderr
+        )));
+    }
+
+    let output_str = String::from_utf8(output.stdout).map_err(Error::Utf8)?;
+
+    Ok(parse_test_list(&output_str))
 }
@@ -0,0 +1,88 @@
+use crate::types::models::{AnalysisDiff, IsotarpAnalysis, TestChange};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+/// Map each covered line of a file in an analysis to the tests known to cover it
+///
+/// With a line index every covered line is known. Without one only lines unique to a test
+/// can be attributed, so lines shared by several tests are left out.
+fn covering_tests(analysis: &IsotarpAnalysis, file: &str) -> BTreeMap<u64, Vec<String>> {
+    if let Some(lines) = analysis
+        .files
+        .get(file)
+        .and_then(|summary| summary.lines.as_ref())
+    {
+        return lines.clone();
+    }
+
+    let mut lines: BTreeMap<u64, Vec<String>> = BTreeMap::new();
+    for (test_name, test_analysis) in &analysis.tests {
+        if let Some(file_analysis) = test_analysis.files.get(file) {
+            for line in &file_analysis.unique_lines {
+                lines.entry(*line).or_default().push(test_name.clone());
+            }
+        }
+    }
+    for tests in lines.values_mut() {
+        tests.sort();
+    }
+    lines
+}
+
+/// Compare two analyses: tests added and removed, tests whose coverage changed, and lines
+/// that lost their last covering test
+pub fn diff_analyses(old: &IsotarpAnalysis, new: &IsotarpAnalysis) -> AnalysisDiff {
+    let old_tests: BTreeSet<&String> = old.tests.keys().collect();
+    let new_tests: BTreeSet<&String> = new.tests.keys().collect();
+
+    let added_tests = new_tests
+        .difference(&old_tests)
+        .map(|test_name| test_name.to_string())
+        .collect();
+    let removed_tests = old_tests
+        .difference(&new_tests)
+        .map(|test_name| test_name.to_string())
+        .collect();
+
+    let changed_tests = old_tests
+        .intersection(&new_tests)
+        .filter_map(|test_name| {
+            let old_analysis = &old.tests[*test_name];
+            let new_analysis = &new.tests[*test_name];
+            let changed = old_analysis.total_covered_lines != new_analysis.total_covered_lines
+                || old_analysis.unique_covered_lines != new_analysis.unique_covered_lines;
+            changed.then(|| TestChange {
+                test: test_name.to_string(),
+                old_total_covered_lines: old_analysis.total_covered_lines,
+                new_total_covered_lines: new_analysis.total_covered_lines,
+                old_unique_covered_lines: old_analysis.unique_covered_lines,
+                new_unique_covered_lines: new_analysis.unique_covered_lines,
+            })
+        })
+        .collect();
+
+    // A line lost its last covering test if it was covered before and is coverable but
+    // uncovered now. Files missing from the new analysis were removed or renamed, so their
+    // line numbers cannot be compared.
+    let mut lost_lines = BTreeMap::new();
+    for (file, new_summary) in &new.files {
+        let uncovered: HashSet<u64> = new_summary.uncovered_lines.iter().copied().collect();
+        if uncovered.is_empty() {
+            continue;
+        }
+
+        let lost: BTreeMap<u64, Vec<String>> = covering_tests(old, file)
+            .into_iter()
+            .filter(|(line, _)| uncovered.contains(line))
+            .collect();
+        if !lost.is_empty() {
+            lost_lines.insert(file.clone(), lost);
+        }
+    }
+
+    AnalysisDiff {
+        added_tests,
+        removed_tests,
+        changed_tests,
+        lost_lines,
+    }
+}
@@ -0,0 +1,12 @@
+pub mod cobertura;
+pub mod html;
+pub mod lcov;
+pub mod markdown;
+pub mod table;
+
+// Re-export main functions
+pub use cobertura::render_cobertura;
+pub use html::render_html;
+pub use lcov::render_lcov;
+pub use markdown::render_markdown;
+pub use table::{render_coverage_table, render_line_table};
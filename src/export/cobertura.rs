@@ -0,0 +1,189 @@
+use crate::coverage::analysis::build_line_index;
+use crate::export::html::escape_html;
+use crate::types::models::{CollectedCoverage, FileSummary, IsotarpAnalysis};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Fraction of lines covered, formatted as Cobertura expects
+fn rate(covered: usize, valid: usize) -> String {
+    if valid == 0 {
+        "0".to_string()
+    } else {
+        format!("{:.4}", covered as f64 / valid as f64)
+    }
+}
+
+/// A file's lines, each mapped to the tests covering it
+struct FileLines<'a> {
+    /// Path relative to the source root
+    path: String,
+    summary: Option<&'a FileSummary>,
+    lines: BTreeMap<u64, Vec<&'a str>>,
+}
+
+impl FileLines<'_> {
+    fn covered(&self) -> usize {
+        self.lines
+            .values()
+            .filter(|tests| !tests.is_empty())
+            .count()
+    }
+}
+
+fn write_lines(out: &mut String, lines: &BTreeMap<u64, Vec<&str>>, indent: &str) {
+    let _ = writeln!(out, "{}<lines>", indent);
+    for (line, tests) in lines {
+        let _ = write!(
+            out,
+            "{}  <line number=\"{}\" hits=\"{}\" branch=\"false\"",
+            indent,
+            line,
+            tests.len()
+        );
+        if !tests.is_empty() {
+            let _ = write!(out, " tests=\"{}\"", escape_html(&tests.join(" ")));
+        }
+        let _ = writeln!(out, "/>");
+    }
+    let _ = writeln!(out, "{}</lines>", indent);
+}
+
+/// Render the union of all per-test coverage as a Cobertura XML report
+///
+/// Each line's hit count is the number of tests covering it, and the covering tests are listed,
+/// separated by spaces, in the line's `tests` attribute. `root` is the workspace root that file
+/// names are relative to, and each directory becomes a package.
+pub fn render_cobertura(
+    coverage: &CollectedCoverage,
+    analysis: &IsotarpAnalysis,
+    root: &Path,
+) -> String {
+    let line_index = build_line_index(&coverage.tests);
+
+    let mut file_names: BTreeSet<&str> = coverage.coverable.keys().map(String::as_str).collect();
+    file_names.extend(line_index.keys().map(String::as_str));
+
+    // Group files into packages by their directory
+    let mut packages: BTreeMap<String, Vec<FileLines>> = BTreeMap::new();
+    for file in file_names {
+        let mut lines: BTreeMap<u64, Vec<&str>> = coverage
+            .coverable
+            .get(file)
+            .map(|coverable| coverable.iter().map(|line| (*line, Vec::new())).collect())
+            .unwrap_or_default();
+        for (line, tests) in line_index.get(file).into_iter().flatten() {
+            let mut tests: Vec<&str> = tests.iter().map(String::as_str).collect();
+            tests.sort();
+            lines.insert(*line, tests);
+        }
+
+        let package = Path::new(file)
+            .parent()
+            .map(|parent| parent.to_string_lossy().replace(['/', '\\'], "."))
+            .filter(|package| !package.is_empty())
+            .unwrap_or_else(|| ".".to_string());
+        packages.entry(package).or_default().push(FileLines {
+            path: file.to_string(),
+            summary: analysis.files.get(file),
+            lines,
+        });
+    }
+
+    let files = packages.values().flatten();
+    let lines_valid: usize = files.clone().map(|file| file.lines.len()).sum();
+    let lines_covered: usize = files.map(FileLines::covered).sum();
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis())
+        .unwrap_or_default();
+
+    let mut out = String::new();
+    let _ = writeln!(out, "<?xml version=\"1.0\" ?>");
+    let _ = writeln!(
+        out,
+        "<!DOCTYPE coverage SYSTEM \"http://cobertura.sourceforge.net/xml/coverage-04.dtd\">"
+    );
+    let _ = writeln!(
+        out,
+        "<coverage lines-valid=\"{}\" lines-covered=\"{}\" line-rate=\"{}\" branches-valid=\"0\" branches-covered=\"0\" branch-rate=\"0\" complexity=\"0\" version=\"isotarp {}\" timestamp=\"{}\">",
+        lines_valid,
+        lines_covered,
+        rate(lines_covered, lines_valid),
+        env!("CARGO_PKG_VERSION"),
+        timestamp
+    );
+    let _ = writeln!(out, "  <sources>");
+    let _ = writeln!(
+        out,
+        "    <source>{}</source>",
+        escape_html(&root.to_string_lossy())
+    );
+    let _ = writeln!(out, "  </sources>");
+    let _ = writeln!(out, "  <packages>");
+
+    for (package, files) in &packages {
+        let valid: usize = files.iter().map(|file| file.lines.len()).sum();
+        let covered: usize = files.iter().map(FileLines::covered).sum();
+        let _ = writeln!(
+            out,
+            "    <package name=\"{}\" line-rate=\"{}\" branch-rate=\"0\" complexity=\"0\">",
+            escape_html(package),
+            rate(covered, valid)
+        );
+        let _ = writeln!(out, "      <classes>");
+
+        for file in files {
+            let class_name = Path::new(&file.path)
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| file.path.clone());
+            let _ = writeln!(
+                out,
+                "        <class name=\"{}\" filename=\"{}\" line-rate=\"{}\" branch-rate=\"0\" complexity=\"0\">",
+                escape_html(&class_name),
+                escape_html(&file.path),
+                rate(file.covered(), file.lines.len())
+            );
+
+            // Functions are known if the file's source could be parsed
+            let _ = writeln!(out, "          <methods>");
+            for (name, function) in file
+                .summary
+                .map(|summary| &summary.functions)
+                .into_iter()
+                .flatten()
+            {
+                let function_lines: BTreeMap<u64, Vec<&str>> = file
+                    .lines
+                    .range(function.start_line..=function.end_line)
+                    .map(|(line, tests)| (*line, tests.clone()))
+                    .collect();
+                let _ = writeln!(
+                    out,
+                    "            <method name=\"{}\" signature=\"\" line-rate=\"{}\" branch-rate=\"0\" complexity=\"0\">",
+                    escape_html(name),
+                    rate(
+                        function.covered_lines as usize,
+                        function.coverable_lines as usize
+                    )
+                );
+                write_lines(&mut out, &function_lines, "              ");
+                let _ = writeln!(out, "            </method>");
+            }
+            let _ = writeln!(out, "          </methods>");
+
+            write_lines(&mut out, &file.lines, "          ");
+            let _ = writeln!(out, "        </class>");
+        }
+
+        let _ = writeln!(out, "      </classes>");
+        let _ = writeln!(out, "    </package>");
+    }
+
+    let _ = writeln!(out, "  </packages>");
+    let _ = writeln!(out, "</coverage>");
+
+    out
+}
@@ -0,0 +1,246 @@
+use crate::coverage::analysis::build_line_index;
+use crate::types::models::{CollectedCoverage, IsotarpAnalysis};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write;
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; color: #222; }
+table { border-collapse: collapse; }
+.index td, .index th { padding: 0.2em 0.8em; text-align: right; }
+.index td:first-child, .index th:first-child { text-align: left; }
+.swatch { display: inline-block; width: 0.9em; height: 0.9em; margin-right: 0.4em;
+  vertical-align: middle; background: hsl(var(--hue), 70%, 80%); }
+.source { width: 100%; font-family: monospace; font-size: 0.9em; }
+.source td { padding: 0 0.5em; white-space: pre; vertical-align: top; }
+.source .count, .source .number { text-align: right; color: #888; user-select: none; }
+.source .code { width: 100%; }
+tr.covered { background: #e6f4e6; cursor: pointer; }
+tr.unique { background: hsl(var(--hue), 70%, 85%); cursor: pointer; }
+tr.uncovered { background: #f8dede; }
+tr.selected { outline: 2px solid #333; }
+#panel { position: fixed; right: 1em; bottom: 1em; max-width: 40em; max-height: 40vh;
+  overflow: auto; padding: 0.8em; background: #fff; border: 1px solid #888;
+  box-shadow: 0 2px 8px rgba(0, 0, 0, 0.2); display: none; }
+";
+
+const SCRIPT: &str = "
+const panel = document.getElementById('panel');
+let selected = null;
+function show(row) {
+  const tests = row.dataset.tests.split(' ').map((index) => TESTS[index]);
+  panel.textContent = '';
+  const heading = document.createElement('strong');
+  heading.textContent = row.dataset.location + ' covered by ' + tests.length + ' test(s)';
+  panel.appendChild(heading);
+  const list = document.createElement('ul');
+  for (const test of tests) {
+    const item = document.createElement('li');
+    item.textContent = test;
+    list.appendChild(item);
+  }
+  panel.appendChild(list);
+  panel.style.display = 'block';
+}
+for (const row of document.querySelectorAll('tr[data-tests]')) {
+  row.addEventListener('mouseenter', () => { if (!selected) show(row); });
+  row.addEventListener('mouseleave', () => { if (!selected) panel.style.display = 'none'; });
+  row.addEventListener('click', () => {
+    if (selected) selected.classList.remove('selected');
+    selected = selected === row ? null : row;
+    if (selected) { selected.classList.add('selected'); show(row); }
+  });
+}
+";
+
+/// Escape text for inclusion in HTML or XML
+pub(crate) fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Pick a distinct, stable hue for each test from its position in the sorted test list
+fn test_hue(index: usize) -> u32 {
+    // Stepping by the golden angle keeps neighbouring tests' colours far apart
+    ((index as f64 * 137.508) % 360.0) as u32
+}
+
+/// Render a self-contained HTML report of each file's source, annotated by covering tests
+///
+/// Each line's gutter shows how many tests cover it, hovering or clicking a line lists those
+/// tests, and lines only one test covers are highlighted in that test's colour.
+pub fn render_html(coverage: &CollectedCoverage, analysis: &IsotarpAnalysis) -> String {
+    let line_index = build_line_index(&coverage.tests);
+
+    let mut test_names: Vec<&String> = coverage.tests.keys().collect();
+    test_names.sort();
+    let test_ids: HashMap<&str, usize> = test_names
+        .iter()
+        .enumerate()
+        .map(|(index, test_name)| (test_name.as_str(), index))
+        .collect();
+
+    let mut files: Vec<&String> = analysis.files.keys().collect();
+    files.sort();
+
+    let mut out = String::new();
+    let title = format!("isotarp: {}", escape_html(&analysis.package));
+    let _ = writeln!(out, "<!DOCTYPE html>");
+    let _ = writeln!(out, "<html lang=\"en\">");
+    let _ = writeln!(
+        out,
+        "<head><meta charset=\"utf-8\"><title>{}</title><style>{}</style></head>",
+        title, STYLE
+    );
+    let _ = writeln!(out, "<body>");
+    let _ = writeln!(
+        out,
+        "<h1>Per-test coverage of {}</h1>",
+        escape_html(&analysis.package)
+    );
+
+    let totals = &analysis.totals;
+    let _ = writeln!(
+        out,
+        "<p>{} tests cover {} of {} coverable lines; {} lines are uncovered.</p>",
+        test_names.len(),
+        totals.covered_lines,
+        totals.coverable_lines,
+        totals.uncovered_lines
+    );
+
+    // Index of files
+    let _ = writeln!(out, "<h2>Files</h2>");
+    let _ = writeln!(
+        out,
+        "<table class=\"index\"><tr><th>File</th><th>Coverable</th><th>Covered</th><th>Uncovered</th></tr>"
+    );
+    for (file_id, file) in files.iter().enumerate() {
+        let summary = &analysis.files[*file];
+        let _ = writeln!(
+            out,
+            "<tr><td><a href=\"#file-{}\">{}</a></td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            file_id,
+            escape_html(file),
+            summary.coverable_lines,
+            summary.covered_lines,
+            summary.uncovered_lines.len()
+        );
+    }
+    let _ = writeln!(out, "</table>");
+
+    // Legend of the colour owning each test's unique lines
+    let _ = writeln!(out, "<h2>Tests</h2>");
+    let _ = writeln!(
+        out,
+        "<table class=\"index\"><tr><th>Test</th><th>Covered</th><th>Unique</th></tr>"
+    );
+    for (test_id, test_name) in test_names.iter().enumerate() {
+        let (covered, unique) = analysis
+            .tests
+            .get(test_name.as_str())
+            .map(|stats| (stats.total_covered_lines, stats.unique_covered_lines))
+            .unwrap_or_default();
+        let _ = writeln!(
+            out,
+            "<tr><td><span class=\"swatch\" style=\"--hue: {}\"></span>{}</td><td>{}</td><td>{}</td></tr>",
+            test_hue(test_id),
+            escape_html(test_name),
+            covered,
+            unique
+        );
+    }
+    let _ = writeln!(out, "</table>");
+
+    // Annotated source of each file
+    let empty = BTreeMap::new();
+    for (file_id, file) in files.iter().enumerate() {
+        let uncovered: HashSet<u64> = analysis.files[*file]
+            .uncovered_lines
+            .iter()
+            .copied()
+            .collect();
+        let lines = line_index.get(*file).unwrap_or(&empty);
+        let _ = writeln!(
+            out,
+            "<section id=\"file-{}\"><h2>{}</h2>",
+            file_id,
+            escape_html(file)
+        );
+
+        let Some(source) = coverage.sources.get(*file) else {
+            let _ = writeln!(out, "<p>Source not available.</p></section>");
+            continue;
+        };
+
+        let _ = writeln!(out, "<table class=\"source\">");
+        for (number, code) in (1u64..).zip(source.lines()) {
+            let code = escape_html(code);
+            match lines.get(&number) {
+                Some(tests) => {
+                    let ids: Vec<String> = tests
+                        .iter()
+                        .map(|test_name| test_ids[test_name.as_str()].to_string())
+                        .collect();
+                    let (class, style) = match tests.as_slice() {
+                        [owner] => (
+                            "unique",
+                            format!(" style=\"--hue: {}\"", test_hue(test_ids[owner.as_str()])),
+                        ),
+                        _ => ("covered", String::new()),
+                    };
+                    let _ = writeln!(
+                        out,
+                        "<tr class=\"{}\"{} data-tests=\"{}\" data-location=\"Line {}\"><td class=\"count\">{}</td><td class=\"number\">{}</td><td class=\"code\">{}</td></tr>",
+                        class,
+                        style,
+                        ids.join(" "),
+                        number,
+                        tests.len(),
+                        number,
+                        code
+                    );
+                }
+                None if uncovered.contains(&number) => {
+                    let _ = writeln!(
+                        out,
+                        "<tr class=\"uncovered\"><td class=\"count\">0</td><td class=\"number\">{}</td><td class=\"code\">{}</td></tr>",
+                        number, code
+                    );
+                }
+                None => {
+                    let _ = writeln!(
+                        out,
+                        "<tr><td class=\"count\"></td><td class=\"number\">{}</td><td class=\"code\">{}</td></tr>",
+                        number, code
+                    );
+                }
+            }
+        }
+        let _ = writeln!(out, "</table></section>");
+    }
+
+    // Test names are looked up by index, so each name is written once rather than per line
+    let names_json = serde_json::to_string(&test_names)
+        .unwrap_or_else(|_| "[]".to_string())
+        .replace('<', "\\u003c");
+    let _ = writeln!(out, "<div id=\"panel\"></div>");
+    let _ = writeln!(
+        out,
+        "<script>const TESTS = {};{}</script>",
+        names_json, SCRIPT
+    );
+    let _ = writeln!(out, "</body>");
+    let _ = writeln!(out, "</html>");
+
+    out
+}
@@ -0,0 +1,143 @@
+use crate::types::models::{CollectedCoverage, IsotarpAnalysis};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fmt::Write;
+
+/// Make a test name valid as an LCOV test name, which may only contain letters, digits
+/// and underscores
+/// Example: "tests::test_foo" -> "tests__test_foo"
+pub fn lcov_test_name(test_name: &str) -> String {
+    test_name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+/// Give each test a distinct LCOV test name, suffixing `_2`, `_3`, ... to names that only
+/// differ in characters LCOV does not allow, e.g. "a::b_c" and "a::b::c"
+///
+/// Names are assigned in the given order, so pass the tests sorted for stable output.
+fn unique_lcov_test_names(test_names: &[&String]) -> Vec<String> {
+    let mut used = HashSet::new();
+    test_names
+        .iter()
+        .map(|test_name| {
+            let base = lcov_test_name(test_name);
+            let mut name = base.clone();
+            let mut suffix = 2;
+            while used.contains(&name) {
+                name = format!("{}_{}", base, suffix);
+                suffix += 1;
+            }
+            used.insert(name.clone());
+            name
+        })
+        .collect()
+}
+
+/// Write one LCOV record for a file, with a test's hit count on each coverable line
+fn write_record(
+    out: &mut String,
+    file: &str,
+    coverable: &BTreeSet<u64>,
+    hits: &BTreeMap<u64, u64>,
+    analysis: &IsotarpAnalysis,
+) {
+    let _ = writeln!(out, "SF:{}", file);
+
+    // Functions are known if the file's source could be parsed
+    if let Some(summary) = analysis
+        .files
+        .get(file)
+        .filter(|summary| !summary.functions.is_empty())
+    {
+        for (name, function) in &summary.functions {
+            let _ = writeln!(out, "FN:{},{}", function.start_line, name);
+        }
+        let mut functions_hit = 0;
+        for (name, function) in &summary.functions {
+            let count = hits
+                .range(function.start_line..=function.end_line)
+                .map(|(_, count)| *count)
+                .max()
+                .unwrap_or(0);
+            if count > 0 {
+                functions_hit += 1;
+            }
+            let _ = writeln!(out, "FNDA:{},{}", count, name);
+        }
+        let _ = writeln!(out, "FNF:{}", summary.functions.len());
+        let _ = writeln!(out, "FNH:{}", functions_hit);
+    }
+
+    for line in coverable {
+        let _ = writeln!(out, "DA:{},{}", line, hits.get(line).copied().unwrap_or(0));
+    }
+    let _ = writeln!(out, "LF:{}", coverable.len());
+    let _ = writeln!(out, "LH:{}", hits.len());
+    let _ = writeln!(out, "end_of_record");
+}
+
+/// Render per-test coverage as an LCOV tracefile with one `TN:` section per test
+///
+/// Each test's section holds a record for every file it covers, listing all of the file's
+/// coverable lines so that tools can show the test's coverage on its own. Files no test
+/// covers are written in a final section with an empty test name, so the tracefile's totals
+/// match the analysis.
+pub fn render_lcov(coverage: &CollectedCoverage, analysis: &IsotarpAnalysis) -> String {
+    let mut out = String::new();
+
+    let coverable: BTreeMap<&str, BTreeSet<u64>> = coverage
+        .coverable
+        .iter()
+        .map(|(file, lines)| (file.as_str(), lines.iter().copied().collect()))
+        .collect();
+
+    let mut test_names: Vec<&String> = coverage.tests.keys().collect();
+    test_names.sort();
+
+    let lcov_names = unique_lcov_test_names(&test_names);
+
+    let mut covered_files = HashSet::new();
+    for (test_name, lcov_name) in test_names.into_iter().zip(lcov_names) {
+        let files = &coverage.tests[test_name];
+        let mut file_names: Vec<&String> = files
+            .iter()
+            .filter(|(_, lines)| !lines.is_empty())
+            .map(|(file, _)| file)
+            .collect();
+        if file_names.is_empty() {
+            continue;
+        }
+        file_names.sort();
+
+        let _ = writeln!(out, "TN:{}", lcov_name);
+        for file in file_names {
+            covered_files.insert(file.as_str());
+            let test_hits = coverage.hits.get(test_name).and_then(|hits| hits.get(file));
+            // Lines without a recorded hit count were still hit at least once
+            let hits: BTreeMap<u64, u64> = files[file]
+                .iter()
+                .map(|line| {
+                    let count = test_hits.and_then(|hits| hits.get(line)).copied();
+                    (*line, count.unwrap_or(1).max(1))
+                })
+                .collect();
+            let mut lines = coverable.get(file.as_str()).cloned().unwrap_or_default();
+            lines.extend(hits.keys());
+            write_record(&mut out, file, &lines, &hits, analysis);
+        }
+    }
+
+    let uncovered_files: Vec<(&&str, &BTreeSet<u64>)> = coverable
+        .iter()
+        .filter(|(file, _)| !covered_files.contains(**file))
+        .collect();
+    if !uncovered_files.is_empty() {
+        let _ = writeln!(out, "TN:");
+        for (file, lines) in uncovered_files {
+            write_record(&mut out, file, lines, &BTreeMap::new(), analysis);
+        }
+    }
+
+    out
+}
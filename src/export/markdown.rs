@@ -0,0 +1,223 @@
+use crate::export::html::escape_html;
+use crate::types::models::{IsotarpAnalysis, TestCoverageAnalysis};
+use crate::utils::ranges::format_line_ranges;
+use std::fmt::Write;
+
+/// Wrap text in a Markdown code span, fenced by more backticks than it contains in a row
+fn code_span(text: &str) -> String {
+    let longest_run = text
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or_default();
+    let fence = "`".repeat(longest_run + 1);
+    // A space keeps a leading or trailing backtick from merging into the fence
+    let padding = if text.starts_with('`') || text.ends_with('`') {
+        " "
+    } else {
+        ""
+    };
+    format!("{fence}{padding}{text}{padding}{fence}")
+}
+
+/// Escape text for a Markdown table cell, wrapped in a code span
+fn code_cell(text: &str) -> String {
+    code_span(text).replace('|', "\\|")
+}
+
+fn percentage(part: u32, whole: u32) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 / whole as f64 * 100.0
+    }
+}
+
+/// Open a collapsible section with a bold summary line
+fn open_details(out: &mut String, summary: &str, open: bool) {
+    let _ = writeln!(
+        out,
+        "<details{}>\n<summary><strong>{}</strong></summary>\n",
+        if open { " open" } else { "" },
+        summary
+    );
+}
+
+fn close_details(out: &mut String) {
+    let _ = writeln!(out, "</details>\n");
+}
+
+/// Write a table of the files in which a test covers lines no other test does
+fn write_unique_files(out: &mut String, stats: &TestCoverageAnalysis) {
+    let mut files: Vec<_> = stats
+        .files
+        .iter()
+        .filter(|(_, file_stats)| !file_stats.unique_lines.is_empty())
+        .collect();
+    files.sort_by_key(|(file, _)| *file);
+
+    let _ = writeln!(out, "| File | Unique lines | Lines |");
+    let _ = writeln!(out, "| --- | ---: | --- |");
+    for (file, file_stats) in files {
+        let mut lines = file_stats.unique_lines.clone();
+        lines.sort();
+        let _ = writeln!(
+            out,
+            "| {} | {} | {} |",
+            code_cell(file),
+            file_stats.unique_covered_lines,
+            format_line_ranges(&lines)
+        );
+    }
+    let _ = writeln!(out);
+}
+
+/// Render the analysis summary as GitHub-flavoured Markdown, e.g. for a pull request comment
+///
+/// Each category of tests is a table in a collapsible section. With `per_file`, each test with
+/// unique coverage also lists the files holding its unique lines, and a table of every file's
+/// coverage and uncovered lines is added.
+pub fn render_markdown(analysis: &IsotarpAnalysis, per_file: bool) -> String {
+    let mut out = String::new();
+
+    let _ = writeln!(
+        out,
+        "## Isotarp analysis of {}\n",
+        code_span(&analysis.package)
+    );
+
+    let totals = &analysis.totals;
+    let _ = writeln!(
+        out,
+        "{} tests cover **{} of {}** coverable lines ({:.1}%), leaving {} uncovered.\n",
+        analysis.tests.len(),
+        totals.covered_lines,
+        totals.coverable_lines,
+        percentage(totals.covered_lines, totals.coverable_lines),
+        totals.uncovered_lines
+    );
+
+    // Separate tests into the same categories as the command-line summary
+    let mut with_unique = Vec::new();
+    let mut without_unique = Vec::new();
+    let mut without_coverage = Vec::new();
+    for (test_name, stats) in &analysis.tests {
+        if stats.unique_covered_lines > 0 {
+            with_unique.push((test_name, stats));
+        } else if stats.total_covered_lines > 0 {
+            without_unique.push((test_name, stats));
+        } else {
+            without_coverage.push(test_name);
+        }
+    }
+    with_unique.sort_by_key(|(test_name, stats)| {
+        (std::cmp::Reverse(stats.unique_covered_lines), *test_name)
+    });
+    without_unique.sort_by_key(|(test_name, _)| *test_name);
+    without_coverage.sort();
+
+    if !with_unique.is_empty() {
+        open_details(
+            &mut out,
+            &format!("Tests with unique line coverage ({})", with_unique.len()),
+            true,
+        );
+        let _ = writeln!(
+            out,
+            "| Test | Unique lines | Covered lines | Unique share |"
+        );
+        let _ = writeln!(out, "| --- | ---: | ---: | ---: |");
+        for (test_name, stats) in &with_unique {
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {:.1}% |",
+                code_cell(test_name),
+                stats.unique_covered_lines,
+                stats.total_covered_lines,
+                percentage(stats.unique_covered_lines, stats.total_covered_lines)
+            );
+        }
+        let _ = writeln!(out);
+
+        if per_file {
+            for (test_name, stats) in &with_unique {
+                open_details(
+                    &mut out,
+                    &format!("<code>{}</code>", escape_html(test_name)),
+                    false,
+                );
+                write_unique_files(&mut out, stats);
+                close_details(&mut out);
+            }
+        }
+        close_details(&mut out);
+    }
+
+    if !without_unique.is_empty() {
+        open_details(
+            &mut out,
+            &format!("Tests with NO unique coverage ({})", without_unique.len()),
+            true,
+        );
+        let _ = writeln!(out, "| Test | Covered lines | Subsumed by |");
+        let _ = writeln!(out, "| --- | ---: | --- |");
+        for (test_name, stats) in &without_unique {
+            let subsuming: Vec<String> = stats
+                .subsumed_by
+                .iter()
+                .map(|other| {
+                    if other.equal {
+                        format!("{} (identical)", code_cell(&other.test))
+                    } else {
+                        code_cell(&other.test)
+                    }
+                })
+                .collect();
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} |",
+                code_cell(test_name),
+                stats.total_covered_lines,
+                subsuming.join(", ")
+            );
+        }
+        let _ = writeln!(out);
+        close_details(&mut out);
+    }
+
+    if !without_coverage.is_empty() {
+        open_details(
+            &mut out,
+            &format!("Tests with NO code coverage ({})", without_coverage.len()),
+            true,
+        );
+        for test_name in &without_coverage {
+            let _ = writeln!(out, "- {}", code_span(test_name));
+        }
+        let _ = writeln!(out);
+        close_details(&mut out);
+    }
+
+    if per_file && !analysis.files.is_empty() {
+        let mut files: Vec<_> = analysis.files.iter().collect();
+        files.sort_by_key(|(file, _)| *file);
+
+        open_details(&mut out, &format!("Files ({})", files.len()), false);
+        let _ = writeln!(out, "| File | Covered | Coverable | Uncovered lines |");
+        let _ = writeln!(out, "| --- | ---: | ---: | --- |");
+        for (file, summary) in files {
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {} |",
+                code_cell(file),
+                summary.covered_lines,
+                summary.coverable_lines,
+                format_line_ranges(&summary.uncovered_lines)
+            );
+        }
+        let _ = writeln!(out);
+        close_details(&mut out);
+    }
+
+    out
+}
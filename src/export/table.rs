@@ -0,0 +1,139 @@
+use crate::types::models::{CollectedCoverage, FileCoverageAnalysis, IsotarpAnalysis};
+use crate::utils::ranges::format_line_ranges;
+use std::collections::HashSet;
+
+/// Quote a field if it contains the delimiter, a quote or a line break, as in RFC 4180
+fn field(value: &str, delimiter: char) -> String {
+    if value.contains([delimiter, '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn write_row(out: &mut String, fields: &[String], delimiter: char) {
+    let row: Vec<String> = fields.iter().map(|value| field(value, delimiter)).collect();
+    out.push_str(&row.join(&delimiter.to_string()));
+    out.push('\n');
+}
+
+/// Every (test, file) pair in the analysis, sorted by test then file
+fn test_files(analysis: &IsotarpAnalysis) -> Vec<(&str, &str, &FileCoverageAnalysis)> {
+    let mut rows: Vec<_> = analysis
+        .tests
+        .iter()
+        .flat_map(|(test_name, stats)| {
+            stats
+                .files
+                .iter()
+                .map(move |(file, file_stats)| (test_name.as_str(), file.as_str(), file_stats))
+        })
+        .collect();
+    rows.sort_by_key(|(test_name, file, _)| (*test_name, *file));
+    rows
+}
+
+/// Render the test × file coverage matrix, one row per test and file it covers
+///
+/// Columns are `test`, `file`, `covered_lines`, `unique_lines` and `unique_line_ranges`.
+/// Use `,` as the delimiter for CSV or `\t` for TSV.
+pub fn render_coverage_table(analysis: &IsotarpAnalysis, delimiter: char) -> String {
+    let mut out = String::new();
+    write_row(
+        &mut out,
+        &[
+            "test".to_string(),
+            "file".to_string(),
+            "covered_lines".to_string(),
+            "unique_lines".to_string(),
+            "unique_line_ranges".to_string(),
+        ],
+        delimiter,
+    );
+
+    for (test_name, file, file_stats) in test_files(analysis) {
+        let mut unique_lines = file_stats.unique_lines.clone();
+        unique_lines.sort();
+        write_row(
+            &mut out,
+            &[
+                test_name.to_string(),
+                file.to_string(),
+                file_stats.total_covered_lines.to_string(),
+                file_stats.unique_covered_lines.to_string(),
+                format_line_ranges(&unique_lines),
+            ],
+            delimiter,
+        );
+    }
+
+    out
+}
+
+/// Render the long-format table of covered lines, one row per test, file and line
+///
+/// Columns are `test`, `file`, `line`, `unique` and `hits`. Every covered line is listed from
+/// the collected coverage, whether or not the analysis has a line index; `hits` is left empty
+/// if the line's hit count is unknown.
+pub fn render_line_table(
+    coverage: &CollectedCoverage,
+    analysis: &IsotarpAnalysis,
+    delimiter: char,
+) -> String {
+    let mut out = String::new();
+    write_row(
+        &mut out,
+        &[
+            "test".to_string(),
+            "file".to_string(),
+            "line".to_string(),
+            "unique".to_string(),
+            "hits".to_string(),
+        ],
+        delimiter,
+    );
+
+    let mut rows: Vec<_> = coverage
+        .tests
+        .iter()
+        .flat_map(|(test_name, files)| {
+            files
+                .iter()
+                .map(move |(file, lines)| (test_name.as_str(), file.as_str(), lines))
+        })
+        .collect();
+    rows.sort_by_key(|(test_name, file, _)| (*test_name, *file));
+
+    for (test_name, file, lines) in rows {
+        let unique: HashSet<u64> = analysis
+            .tests
+            .get(test_name)
+            .and_then(|stats| stats.files.get(file))
+            .map(|file_stats| file_stats.unique_lines.iter().copied().collect())
+            .unwrap_or_default();
+        let hits = coverage
+            .hits
+            .get(test_name)
+            .and_then(|files| files.get(file));
+        let mut lines: Vec<u64> = lines.iter().copied().collect();
+        lines.sort();
+
+        for line in lines {
+            write_row(
+                &mut out,
+                &[
+                    test_name.to_string(),
+                    file.to_string(),
+                    line.to_string(),
+                    unique.contains(&line).to_string(),
+                    hits.and_then(|hits| hits.get(&line))
+                        .map(|hits| hits.to_string())
+                        .unwrap_or_default(),
+                ],
+                delimiter,
+            );
+        }
+    }
+
+    out
+}
@@ -1,16 +1,29 @@
 // Public API exports
+pub mod affected;
 pub mod cli;
 pub mod coverage;
+pub mod diff;
+pub mod export;
+pub mod report;
 pub mod resolve;
 pub mod types;
 pub mod utils;
 
 // Re-export commonly used items for convenience
-pub use coverage::analysis::{analyze_test_coverage, run_analysis};
-pub use coverage::tarpaulin::{extract_covered_lines, run_isolated_test_coverage};
+pub use coverage::analysis::{
+    analyze_test_coverage, build_analysis, build_line_index, collect_test_coverage,
+    find_subsuming_tests, run_analysis,
+};
+pub use coverage::minimize::minimize_test_suite;
+pub use coverage::similarity::compute_test_similarity;
+pub use coverage::tarpaulin::{
+    extract_coverable_lines, extract_covered_lines, extract_line_hits, extract_sources,
+    extract_test_coverage, run_isolated_test_coverage,
+};
+pub use report::Report;
 pub use types::errors::Error;
 pub use types::models::*;
-pub use utils::io::save_analysis;
+pub use utils::io::{load_analysis, save_analysis};
 pub use utils::paths::{
     artifacts_dir, test_name_to_path_segment, test_output_dir, test_report_path, test_target_dir,
 };
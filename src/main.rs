@@ -1,21 +1,112 @@
 use clap::Parser;
-use isotarp::cli::{Cli, Commands, execute_analyze_command, execute_list_command};
+use isotarp::cli::{
+    AnalyzeOptions, Cli, Commands, SimilarityOptions, execute_affected_command,
+    execute_analyze_command, execute_diff_command, execute_list_command, execute_merge_command,
+    execute_minimize_command, execute_similarity_command,
+};
+use isotarp::types::models::CollectOptions;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
 
     match cli.command {
-        Commands::List { package } => {
-            execute_list_command(&package)?;
+        Commands::List { packages, doctests } => {
+            execute_list_command(&packages.resolve()?, doctests)?;
         }
         Commands::Analyze {
-            package,
+            packages,
             tests,
             output_dir,
             report,
-            target_mode,
+            collect,
+            line_index,
+            resume,
+            include,
+            shard,
+            from_reports,
+            exports,
         } => {
-            execute_analyze_command(&package, tests, &output_dir, &report, target_mode)?;
+            execute_analyze_command(
+                &packages.resolve()?,
+                tests,
+                &output_dir,
+                &report,
+                CollectOptions {
+                    record_progress: true,
+                    resume,
+                    include,
+                    ..collect.options()
+                },
+                AnalyzeOptions {
+                    line_index,
+                    shard,
+                    from_reports,
+                    exports,
+                },
+            )?;
+        }
+        Commands::Minimize {
+            packages,
+            tests,
+            output_dir,
+            report,
+            collect,
+            from_reports,
+        } => {
+            execute_minimize_command(
+                &packages.resolve()?,
+                tests,
+                &output_dir,
+                report.as_deref(),
+                collect.options(),
+                from_reports.as_deref(),
+            )?;
+        }
+        Commands::Similarity {
+            packages,
+            tests,
+            output_dir,
+            report,
+            metric,
+            threshold,
+            collect,
+            from_reports,
+        } => {
+            execute_similarity_command(
+                &packages.resolve()?,
+                tests,
+                &output_dir,
+                &report,
+                collect.options(),
+                SimilarityOptions {
+                    metric,
+                    threshold,
+                    from_reports,
+                },
+            )?;
+        }
+        Commands::Affected {
+            report,
+            base,
+            output,
+        } => {
+            execute_affected_command(&report, &base, output.as_deref())?;
+        }
+        Commands::Diff {
+            old,
+            new,
+            output,
+            fail_on_lost,
+        } => {
+            execute_diff_command(&old, &new, output.as_deref(), fail_on_lost)?;
+        }
+        Commands::Merge {
+            parts,
+            report,
+            line_index,
+            exports,
+        } => {
+            execute_merge_command(&parts, &report, line_index, &exports)?;
         }
     }
 
@@ -0,0 +1,128 @@
+use crate::types::errors::Error;
+use crate::types::models::IsotarpAnalysis;
+use crate::utils::io::load_analysis;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Find the files in an analysis that a path refers to, comparing whole path components
+/// so that absolute report paths match repository-relative paths and vice versa
+pub fn matching_files<'a>(analysis: &'a IsotarpAnalysis, path: &str) -> Vec<&'a str> {
+    let path = Path::new(path);
+    let mut files: Vec<&str> = analysis
+        .files
+        .keys()
+        .filter(|file| {
+            let file_path = Path::new(file.as_str());
+            file_path.ends_with(path) || path.ends_with(file_path)
+        })
+        .map(String::as_str)
+        .collect();
+    files.sort();
+    files
+}
+
+/// A saved analysis, with queries over the tests and lines it records
+///
+/// Results are sorted so they are stable across loads.
+#[derive(Debug)]
+pub struct Report {
+    analysis: IsotarpAnalysis,
+}
+
+impl Report {
+    /// Load a report from a JSON file written by `isotarp analyze`
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        Ok(Self::new(load_analysis(path)?))
+    }
+
+    pub fn new(analysis: IsotarpAnalysis) -> Self {
+        Self { analysis }
+    }
+
+    pub fn analysis(&self) -> &IsotarpAnalysis {
+        &self.analysis
+    }
+
+    pub fn into_analysis(self) -> IsotarpAnalysis {
+        self.analysis
+    }
+
+    /// Whether the report records every covering test of every covered line
+    pub fn has_line_index(&self) -> bool {
+        self.analysis.has_line_index()
+    }
+
+    /// Names of all analyzed tests
+    pub fn tests(&self) -> Vec<&str> {
+        let mut tests: Vec<&str> = self.analysis.tests.keys().map(String::as_str).collect();
+        tests.sort();
+        tests
+    }
+
+    /// Paths of all files with coverable lines
+    pub fn files(&self) -> Vec<&str> {
+        let mut files: Vec<&str> = self.analysis.files.keys().map(String::as_str).collect();
+        files.sort();
+        files
+    }
+
+    /// Files in the report that a path refers to, matched by trailing path components
+    /// Example: "src/lib.rs" matches "/home/user/project/src/lib.rs"
+    pub fn find_files(&self, path: &str) -> Vec<&str> {
+        matching_files(&self.analysis, path)
+    }
+
+    /// Tests covering a line of a file
+    ///
+    /// Returns `None` if the file is not in the report, or if the report was produced without
+    /// a line index and so does not know every covering test.
+    pub fn tests_for_line(&self, file: &str, line: u64) -> Option<Vec<&str>> {
+        let lines = self.analysis.files.get(file)?.lines.as_ref()?;
+        Some(
+            lines
+                .get(&line)
+                .map(|tests| tests.iter().map(String::as_str).collect())
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Lines covered by a test and by no other, keyed by file
+    ///
+    /// Returns `None` if the test is not in the report.
+    pub fn unique_lines(&self, test: &str) -> Option<BTreeMap<&str, Vec<u64>>> {
+        let test_analysis = self.analysis.tests.get(test)?;
+        Some(
+            test_analysis
+                .files
+                .iter()
+                .filter(|(_, file_analysis)| !file_analysis.unique_lines.is_empty())
+                .map(|(file, file_analysis)| {
+                    let mut lines = file_analysis.unique_lines.clone();
+                    lines.sort();
+                    (file.as_str(), lines)
+                })
+                .collect(),
+        )
+    }
+
+    /// Files in which a test covers at least one line
+    ///
+    /// Returns `None` if the test is not in the report.
+    pub fn files_touched(&self, test: &str) -> Option<Vec<&str>> {
+        let test_analysis = self.analysis.tests.get(test)?;
+        let mut files: Vec<&str> = test_analysis
+            .files
+            .iter()
+            .filter(|(_, file_analysis)| file_analysis.total_covered_lines > 0)
+            .map(|(file, _)| file.as_str())
+            .collect();
+        files.sort();
+        Some(files)
+    }
+}
+
+impl From<IsotarpAnalysis> for Report {
+    fn from(analysis: IsotarpAnalysis) -> Self {
+        Self::new(analysis)
+    }
+}
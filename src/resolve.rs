@@ -1,3 +1,4 @@
+use crate::types::models::{Shard, TestId};
 use wildmatch::WildMatch;
 
 // Categorize patterns by type for more efficient processing
@@ -45,8 +46,9 @@ pub fn resolve_test_patterns(
 
     // Process exact matches first (most efficient)
     for pattern in exact_patterns {
-        if available_tests.contains(&pattern) {
-            selected_tests.push(pattern.clone());
+        let matches = match_test_exactly(available_tests, &pattern);
+        if !matches.is_empty() {
+            selected_tests.extend(matches);
         } else {
             invalid_patterns.push(pattern);
         }
@@ -89,12 +91,21 @@ pub fn resolve_test_patterns(
     (selected_tests, invalid_patterns)
 }
 
-// Match tests by their full path (including ::)
+// Match tests by their qualified name, or by their path within any target
+fn match_test_exactly(available_tests: &[String], pattern: &str) -> Vec<String> {
+    available_tests
+        .iter()
+        .filter(|test| *test == pattern || TestId::path_of(test) == pattern)
+        .cloned()
+        .collect()
+}
+
+// Match tests by their full path (including ::), with or without the target qualifier
 fn match_test_by_path(available_tests: &[String], pattern: &str) -> Vec<String> {
     let wildcard = WildMatch::new(pattern);
     available_tests
         .iter()
-        .filter(|test| wildcard.matches(test))
+        .filter(|test| wildcard.matches(test) || wildcard.matches(TestId::path_of(test)))
         .cloned()
         .collect()
 }
@@ -114,3 +125,45 @@ fn match_test_by_name(available_tests: &[String], pattern: &str) -> Vec<String>
         .cloned()
         .collect()
 }
+
+/// Find the selected tests whose names are prefixes of other available tests, with the tests
+/// they prefix
+///
+/// A plain libtest filter is a substring match, so such a test would also run the others.
+pub fn find_prefix_collisions(
+    available_tests: &[String],
+    test_names: &[String],
+) -> Vec<(String, Vec<String>)> {
+    let mut sorted = available_tests.to_vec();
+    sorted.sort();
+    sorted.dedup();
+
+    let mut collisions = Vec::new();
+    for test_name in test_names {
+        // Names starting with the test name sort directly after it
+        let start = sorted.partition_point(|other| other <= test_name);
+        let prefixed: Vec<String> = sorted[start..]
+            .iter()
+            .take_while(|other| other.starts_with(test_name.as_str()))
+            .cloned()
+            .collect();
+        if !prefixed.is_empty() {
+            collisions.push((test_name.clone(), prefixed));
+        }
+    }
+    collisions
+}
+
+/// Select the tests in one shard, dealing the sorted test names round-robin across shards
+/// so every machine computes the same partition from the same test list
+pub fn shard_tests(test_names: &[String], shard: Shard) -> Vec<String> {
+    let mut sorted = test_names.to_vec();
+    sorted.sort();
+    sorted.dedup();
+    sorted
+        .into_iter()
+        .enumerate()
+        .filter(|(idx, _)| *idx as u32 % shard.count == shard.index - 1)
+        .map(|(_, test_name)| test_name)
+        .collect()
+}
@@ -16,4 +16,21 @@ pub enum Error {
 
     #[error("Command failed: {0}")]
     CommandFailed(String),
+
+    #[error("Merge failed: {0}")]
+    MergeFailed(String),
+
+    #[error("Cannot resume: {0}")]
+    ResumeFailed(String),
+
+    #[error("Reports do not match the workspace: {0}")]
+    ReportMismatch(String),
+
+    #[error("Unsupported report schema version {0} (newest supported is {1})")]
+    UnsupportedSchema(u32, u32),
+
+    #[error(
+        "Outdated report schema version {0} (expected {1}), re-run the analysis with this build"
+    )]
+    OutdatedSchema(u32, u32),
 }
@@ -1,7 +1,40 @@
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
-pub type TestCoverageResult = (String, HashMap<String, HashSet<u64>>);
+pub type TestCoverageResult = (String, TestCoverage);
+
+/// Covered lines per file, keyed by test name
+pub type CoverageMap = HashMap<String, HashMap<String, HashSet<u64>>>;
+
+/// Hit count of each covered line, keyed by file path
+pub type LineHits = HashMap<String, HashMap<u64, u64>>;
+
+/// Coverage extracted from a single test's tarpaulin report
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct TestCoverage {
+    /// Lines hit at least once, keyed by file path
+    pub covered: HashMap<String, HashSet<u64>>,
+    /// Lines tarpaulin instrumented, whether hit or not, keyed by file path
+    pub coverable: HashMap<String, HashSet<u64>>,
+    /// Number of times each covered line was hit
+    pub hits: LineHits,
+}
+
+/// Coverage gathered from the isolated runs of all tests
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct CollectedCoverage {
+    /// Covered lines per file, keyed by test name
+    pub tests: CoverageMap,
+    /// Coverable lines per file, merged across all tests' reports
+    pub coverable: HashMap<String, HashSet<u64>>,
+    /// Hit counts of covered lines, keyed by test name
+    pub hits: HashMap<String, LineHits>,
+    /// Source code of each file, as embedded in the tarpaulin reports
+    pub sources: HashMap<String, String>,
+    /// Crate each file belongs to, if the files of several crates are reported
+    #[serde(default)]
+    pub crates: HashMap<String, String>,
+}
 
 /// Representation of Tarpaulin's JSON output
 #[derive(Debug, Deserialize, Serialize)]
@@ -35,26 +68,266 @@ pub enum LineStat {
 }
 
 /// Analysis of a single test's coverage
-#[derive(Debug, Serialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct TestCoverageAnalysis {
     pub total_covered_lines: u32,
     pub unique_covered_lines: u32,
     pub files: HashMap<String, FileCoverageAnalysis>,
+    pub subsumed_by: Vec<SubsumingTest>,
+    /// Sum of the hit counts of all lines this test covers
+    pub total_hits: u64,
+    /// Lines shared with other tests where this test has strictly the most hits
+    pub dominant_lines: u32,
+    /// Functions that no other test reaches
+    pub unique_functions: u32,
+}
+
+/// Another test whose coverage contains all of this test's covered lines
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SubsumingTest {
+    pub test: String,
+    /// Whether both tests cover exactly the same lines
+    pub equal: bool,
 }
 
 /// Analysis of a file's coverage by a test
-#[derive(Debug, Serialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct FileCoverageAnalysis {
     pub total_covered_lines: u32,
     pub unique_covered_lines: u32,
     pub unique_lines: Vec<u64>,
+    pub total_hits: u64,
+    /// Hit count of every covered line (only with a line index)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line_hits: Option<BTreeMap<u64, u64>>,
+    /// Functions this test reaches, keyed by name within the file
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub functions: BTreeMap<String, FunctionCoverage>,
+}
+
+/// Coverage of a function by a test
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct FunctionCoverage {
+    pub covered_lines: u32,
+    pub unique_covered_lines: u32,
+    /// Whether no other test reaches this function at all
+    pub only_covering_test: bool,
 }
 
+/// Coverage of a file across all tests
+#[derive(Debug, Deserialize, Serialize)]
+pub struct FileSummary {
+    pub coverable_lines: u32,
+    pub covered_lines: u32,
+    /// Coverable lines that no test reaches
+    pub uncovered_lines: Vec<u64>,
+    /// Every covered line mapped to the tests that cover it (only with a line index)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lines: Option<BTreeMap<u64, Vec<String>>>,
+    /// Shared lines mapped to the test with strictly the most hits (only with a line index)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dominant: Option<BTreeMap<u64, DominantTest>>,
+    /// Functions with coverable lines, keyed by name within the file
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub functions: BTreeMap<String, FunctionSummary>,
+}
+
+/// Coverage of a function across all tests
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct FunctionSummary {
+    pub start_line: u64,
+    pub end_line: u64,
+    pub coverable_lines: u32,
+    pub covered_lines: u32,
+    /// Tests reaching at least one line of the function
+    pub tests: Vec<String>,
+}
+
+/// The test exercising a shared line most heavily
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct DominantTest {
+    pub test: String,
+    pub hits: u64,
+    /// Hits on the line summed across all covering tests
+    pub total_hits: u64,
+}
+
+/// Version of the analysis report format written by this build
+///
+/// Bump this whenever a change to the report types would stop older builds from reading it.
+pub const SCHEMA_VERSION: u32 = 6;
+
 /// Complete analysis output
-#[derive(Debug, Serialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct IsotarpAnalysis {
+    /// Report format version, read as 0 for reports written before versioning was introduced so
+    /// they are rejected as outdated
+    #[serde(default)]
+    pub schema_version: u32,
     pub package: String,
     pub tests: HashMap<String, TestCoverageAnalysis>,
+    pub files: HashMap<String, FileSummary>,
+    pub totals: CoverageTotals,
+    /// Coverage of each crate, if the files of several crates are reported
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub crates: BTreeMap<String, CrateSummary>,
+}
+
+impl IsotarpAnalysis {
+    /// Whether the analysis records every covering test of every covered line, as produced
+    /// with `--line-index`
+    ///
+    /// An analysis without files has no index to show for it, so this is false.
+    pub fn has_line_index(&self) -> bool {
+        !self.files.is_empty() && self.files.values().all(|summary| summary.lines.is_some())
+    }
+}
+
+/// Coverage of a crate's files, split by the tests reaching them
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct CrateSummary {
+    pub coverable_lines: u32,
+    pub covered_lines: u32,
+    /// Covered lines that none of the crate's own tests reach, keyed by file, left out if
+    /// none of its own tests were part of the run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub missed_by_own_tests: Option<BTreeMap<String, Vec<u64>>>,
+    /// Coverage of the crate by each test reaching it
+    pub tests: BTreeMap<String, CrateTestCoverage>,
+}
+
+/// Coverage of a crate's files by a single test
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CrateTestCoverage {
+    pub covered_lines: u32,
+    /// Lines of the crate that no other test covers
+    pub unique_lines: u32,
+    /// Lines of the crate that none of the crate's own tests reach, left out if none of its
+    /// own tests were part of the run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub missed_by_own_tests: Option<u32>,
+}
+
+/// Coverage of the package across all tests and files
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct CoverageTotals {
+    pub coverable_lines: u32,
+    pub covered_lines: u32,
+    pub uncovered_lines: u32,
+}
+
+/// A test kept in a minimized suite, with the lines it added when it was selected
+#[derive(Debug, Serialize)]
+pub struct KeptTest {
+    pub name: String,
+    pub new_covered_lines: u32,
+}
+
+/// A minimal subset of tests whose combined coverage equals that of the full suite
+#[derive(Debug, Serialize)]
+pub struct SuiteMinimization {
+    pub total_covered_lines: u32,
+    pub kept: Vec<KeptTest>,
+    pub droppable: Vec<String>,
+}
+
+/// Differences between two analyses of the same package
+#[derive(Debug, Default, Serialize)]
+pub struct AnalysisDiff {
+    pub added_tests: Vec<String>,
+    pub removed_tests: Vec<String>,
+    /// Tests in both analyses whose total or unique covered lines changed
+    pub changed_tests: Vec<TestChange>,
+    /// Lines covered in the old analysis but not in the new one, keyed by file and mapped to
+    /// the tests that used to cover them
+    pub lost_lines: BTreeMap<String, BTreeMap<u64, Vec<String>>>,
+}
+
+/// Change in a test's coverage between two analyses
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TestChange {
+    pub test: String,
+    pub old_total_covered_lines: u32,
+    pub new_total_covered_lines: u32,
+    pub old_unique_covered_lines: u32,
+    pub new_unique_covered_lines: u32,
+}
+
+/// Similarity measure used to group near-duplicate tests
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum SimilarityMetric {
+    /// Shared lines divided by the lines covered by either test
+    #[default]
+    Jaccard,
+    /// Shared lines divided by the lines covered by the smaller test
+    Overlap,
+}
+
+impl std::fmt::Display for SimilarityMetric {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SimilarityMetric::Jaccard => write!(f, "jaccard"),
+            SimilarityMetric::Overlap => write!(f, "overlap"),
+        }
+    }
+}
+
+/// Pairwise similarity of tests' covered lines, with clusters of near-duplicates
+#[derive(Debug, Serialize)]
+pub struct TestSimilarity {
+    /// Test names, in the row and column order of the matrices
+    pub tests: Vec<String>,
+    pub jaccard: Vec<Vec<f64>>,
+    pub overlap: Vec<Vec<f64>>,
+    pub metric: SimilarityMetric,
+    pub threshold: f64,
+    /// Groups of tests linked by a similarity at or above the threshold
+    pub clusters: Vec<Vec<String>>,
+}
+
+/// One of `count` deterministic partitions of a package's tests, numbered from 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Shard {
+    pub index: u32,
+    pub count: u32,
+}
+
+impl std::str::FromStr for Shard {
+    type Err = String;
+
+    /// Parse a shard written as "i/n", e.g. "2/4" for the second of four shards
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid shard '{}', expected i/n with 1 <= i <= n", s);
+        let (index, count) = s.split_once('/').ok_or_else(invalid)?;
+        let index: u32 = index.trim().parse().map_err(|_| invalid())?;
+        let count: u32 = count.trim().parse().map_err(|_| invalid())?;
+        if index == 0 || index > count {
+            return Err(invalid());
+        }
+        Ok(Shard { index, count })
+    }
+}
+
+impl std::fmt::Display for Shard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.index, self.count)
+    }
+}
+
+/// Raw per-test coverage of one shard, to be merged with the other shards into an analysis
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PartialCoverage {
+    #[serde(default)]
+    pub schema_version: u32,
+    pub package: String,
+    pub shard: Shard,
+    /// Every test of the run across all shards, sorted, so shards of different selections
+    /// are not merged
+    pub tests: Vec<String>,
+    /// Whether the run included the package's doctests
+    pub doctests: bool,
+    pub coverage: CollectedCoverage,
 }
 
 /// Mode for managing target directories during test execution
@@ -75,3 +348,174 @@ impl std::fmt::Display for TargetMode {
         }
     }
 }
+
+/// Options controlling how per-test coverage is collected
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CollectOptions {
+    pub target_mode: TargetMode,
+    /// Reuse cached coverage for tests whose binary and sources are unchanged
+    pub use_cache: bool,
+    /// Record completed tests in the output directory's progress manifest, so an interrupted
+    /// run can be resumed
+    pub record_progress: bool,
+    /// Skip tests completed by an earlier, interrupted run in the same output directory
+    pub resume: bool,
+    /// Include the package's doctests alongside its unit and integration tests
+    pub doctests: bool,
+    /// Other local crates whose coverage by the packages' tests is reported too
+    pub include: Vec<String>,
+}
+
+/// Kind of cargo target a test is compiled into
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TargetKind {
+    Lib,
+    Bin,
+    Test,
+    Example,
+    Bench,
+    /// Documentation tests of a library, compiled and run by rustdoc
+    Doc,
+}
+
+impl TargetKind {
+    /// Map the kinds cargo reports for a target to the kind of its test executable
+    /// Every library crate type (e.g. "rlib", "cdylib", "proc-macro") is a "lib" target
+    pub fn from_cargo_kinds(kinds: &[&str]) -> Option<Self> {
+        kinds.iter().find_map(|kind| match *kind {
+            "lib" | "rlib" | "dylib" | "cdylib" | "staticlib" | "proc-macro" => {
+                Some(TargetKind::Lib)
+            }
+            "bin" => Some(TargetKind::Bin),
+            "test" => Some(TargetKind::Test),
+            "example" => Some(TargetKind::Example),
+            "bench" => Some(TargetKind::Bench),
+            _ => None,
+        })
+    }
+}
+
+impl std::str::FromStr for TargetKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "lib" => Ok(TargetKind::Lib),
+            "bin" => Ok(TargetKind::Bin),
+            "test" => Ok(TargetKind::Test),
+            "example" => Ok(TargetKind::Example),
+            "bench" => Ok(TargetKind::Bench),
+            "doc" => Ok(TargetKind::Doc),
+            _ => Err(format!("unknown target kind '{}'", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for TargetKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TargetKind::Lib => write!(f, "lib"),
+            TargetKind::Bin => write!(f, "bin"),
+            TargetKind::Test => write!(f, "test"),
+            TargetKind::Example => write!(f, "example"),
+            TargetKind::Bench => write!(f, "bench"),
+            TargetKind::Doc => write!(f, "doc"),
+        }
+    }
+}
+
+/// A cargo target whose test executable contains tests
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TestTarget {
+    pub kind: TargetKind,
+    pub name: String,
+}
+
+impl TestTarget {
+    /// Arguments selecting only this target, for cargo and tarpaulin
+    /// Example: ["--lib"], ["--test", "integration"]
+    pub fn cargo_args(&self) -> Vec<String> {
+        match self.kind {
+            TargetKind::Lib => vec!["--lib".to_string()],
+            TargetKind::Doc => vec!["--doc".to_string()],
+            kind => vec![format!("--{}", kind), self.name.clone()],
+        }
+    }
+}
+
+/// A compiled test executable and the target it was built from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestBinary {
+    pub target: TestTarget,
+    pub executable: std::path::PathBuf,
+}
+
+/// A test qualified by the target it is compiled into, so that tests with the same path in
+/// different targets stay distinct
+///
+/// Written as "kind:target::path", e.g. "lib:demolib::tests::it_works",
+/// "test:integration::tests::it_works" or "doc:demolib::src/lib.rs - foo (line 12)".
+/// When several packages are analyzed together the package comes first, as in
+/// "demolib/test:integration::tests::it_works".
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TestId {
+    /// Package containing the target, if the analysis spans several packages
+    pub package: Option<String>,
+    pub target: TestTarget,
+    /// Path of the test within its target, as listed by the test harness
+    pub path: String,
+}
+
+impl TestId {
+    /// Get the path of a test within its target, or the name itself if it is not qualified
+    /// Example: "lib:demolib::tests::it_works" -> "tests::it_works"
+    pub fn path_of(test_name: &str) -> &str {
+        match test_name.parse::<TestId>() {
+            Ok(_) => test_name
+                .split_once("::")
+                .map(|(_, path)| path)
+                .unwrap_or(test_name),
+            Err(_) => test_name,
+        }
+    }
+}
+
+impl std::str::FromStr for TestId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid test '{}', expected [package/]kind:target::path", s);
+        let (qualifier, rest) = s.split_once(':').ok_or_else(invalid)?;
+        let (package, kind) = match qualifier.split_once('/') {
+            Some((package, kind)) if !package.is_empty() => (Some(package.to_string()), kind),
+            Some(_) => return Err(invalid()),
+            None => (None, qualifier),
+        };
+        let (name, path) = rest.split_once("::").ok_or_else(invalid)?;
+        // A module path such as "lib::tests::x" is not a target named ":tests"
+        if name.is_empty() || name.contains(':') || path.is_empty() {
+            return Err(invalid());
+        }
+        Ok(TestId {
+            package,
+            target: TestTarget {
+                kind: kind.parse().map_err(|_| invalid())?,
+                name: name.to_string(),
+            },
+            path: path.to_string(),
+        })
+    }
+}
+
+impl std::fmt::Display for TestId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(package) = &self.package {
+            write!(f, "{}/", package)?;
+        }
+        write!(
+            f,
+            "{}:{}::{}",
+            self.target.kind, self.target.name, self.path
+        )
+    }
+}
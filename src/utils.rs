@@ -1,5 +1,7 @@
 pub mod cleanup;
 pub mod io;
+pub mod metadata;
 pub mod paths;
 pub mod pipeline;
+pub mod ranges;
 pub mod target_symlink;
@@ -1,5 +1,5 @@
 use crate::types::errors::Error;
-use crate::types::models::IsotarpAnalysis;
+use crate::types::models::{IsotarpAnalysis, PartialCoverage, SCHEMA_VERSION};
 use serde_json::{Map, Value};
 use std::collections::BTreeMap;
 use std::path::Path;
@@ -34,26 +34,7 @@ fn sort_json_object(map: &Map<String, Value>) -> Value {
     for (key, value) in map {
         let sorted_value = match value {
             Value::Object(obj) => sort_json_object(obj),
-            Value::Array(arr) => {
-                // Sort arrays that contain primitive values if possible
-                let mut sorted_arr = Vec::new();
-                for item in arr {
-                    match item {
-                        Value::Object(obj) => sorted_arr.push(sort_json_object(obj)),
-                        Value::Array(_) => sorted_arr.push(item.clone()), // Not sorting nested arrays
-                        // Sort arrays of numbers
-                        Value::Number(_) => {
-                            let mut numbers: Vec<i64> =
-                                arr.iter().filter_map(|v| v.as_i64()).collect();
-                            numbers.sort();
-                            return Value::Array(numbers.into_iter().map(Value::from).collect());
-                        }
-                        // For other primitive types, just clone
-                        _ => sorted_arr.push(item.clone()),
-                    }
-                }
-                Value::Array(sorted_arr)
-            }
+            Value::Array(arr) => sort_json_array(arr),
             _ => value.clone(),
         };
         sorted_map.insert(key.clone(), sorted_value);
@@ -62,3 +43,81 @@ fn sort_json_object(map: &Map<String, Value>) -> Value {
     // Convert the BTreeMap to a JSON Value
     serde_json::to_value(sorted_map).unwrap_or(Value::Object(map.clone()))
 }
+
+/// Sort an array of numbers, and recursively sort the keys of any objects in other arrays
+fn sort_json_array(arr: &[Value]) -> Value {
+    if !arr.is_empty() && arr.iter().all(Value::is_u64) {
+        let mut numbers: Vec<u64> = arr.iter().filter_map(Value::as_u64).collect();
+        numbers.sort();
+        return Value::Array(numbers.into_iter().map(Value::from).collect());
+    }
+
+    let sorted_arr = arr
+        .iter()
+        .map(|item| match item {
+            Value::Object(obj) => sort_json_object(obj),
+            // Nested arrays and other primitive types are kept as they are
+            _ => item.clone(),
+        })
+        .collect();
+    Value::Array(sorted_arr)
+}
+
+/// Check a report's version before parsing the rest, so reports in any other format fail with a
+/// clear error rather than a missing field or misread paths and test names
+fn check_schema_version(value: Value) -> Result<Value, Error> {
+    let version = value
+        .get("schema_version")
+        .and_then(Value::as_u64)
+        .unwrap_or_default() as u32;
+    if version > SCHEMA_VERSION {
+        return Err(Error::UnsupportedSchema(version, SCHEMA_VERSION));
+    }
+    if version < SCHEMA_VERSION {
+        return Err(Error::OutdatedSchema(version, SCHEMA_VERSION));
+    }
+    Ok(value)
+}
+
+/// Load a previously saved analysis from a JSON file
+pub fn load_analysis(input_path: &Path) -> Result<IsotarpAnalysis, Error> {
+    let content = std::fs::read_to_string(input_path).map_err(|e| {
+        Error::Io(std::io::Error::new(
+            e.kind(),
+            format!(
+                "Failed to read analysis report '{}': {}",
+                input_path.display(),
+                e
+            ),
+        ))
+    })?;
+
+    let value = check_schema_version(serde_json::from_str(&content)?)?;
+    let analysis = serde_json::from_value(value)?;
+    Ok(analysis)
+}
+
+/// Save a shard's raw coverage to a JSON file
+pub fn save_partial(partial: &PartialCoverage, output_path: &Path) -> Result<(), Error> {
+    let json = serde_json::to_string(partial)?;
+    std::fs::write(output_path, json)?;
+    Ok(())
+}
+
+/// Load a shard's raw coverage from a JSON file
+pub fn load_partial(input_path: &Path) -> Result<PartialCoverage, Error> {
+    let content = std::fs::read_to_string(input_path).map_err(|e| {
+        Error::Io(std::io::Error::new(
+            e.kind(),
+            format!(
+                "Failed to read partial coverage '{}': {}",
+                input_path.display(),
+                e
+            ),
+        ))
+    })?;
+
+    let value = check_schema_version(serde_json::from_str(&content)?)?;
+    let partial = serde_json::from_value(value)?;
+    Ok(partial)
+}
@@ -0,0 +1,206 @@
+use crate::types::errors::Error;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Run `cargo metadata` for the workspace in the current directory
+fn cargo_metadata(no_deps: bool) -> Result<String, Error> {
+    let mut args = vec!["metadata", "--format-version", "1"];
+    if no_deps {
+        args.push("--no-deps");
+    }
+    let output = Command::new("cargo")
+        .args(&args)
+        .output()
+        .map_err(|e| Error::CommandFailed(format!("Failed to execute 'cargo metadata': {}", e)))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::CommandFailed(format!(
+            "cargo metadata failed: {}\nStderr: {}",
+            output.status, stderr
+        )));
+    }
+
+    String::from_utf8(output.stdout).map_err(Error::Utf8)
+}
+
+/// Get the names of the member packages of the workspace in the current directory
+pub fn workspace_members() -> Result<Vec<String>, Error> {
+    parse_workspace_members(&cargo_metadata(true)?)
+}
+
+/// Parse the member package names from the output of `cargo metadata --no-deps`
+pub fn parse_workspace_members(metadata: &str) -> Result<Vec<String>, Error> {
+    let metadata: serde_json::Value = serde_json::from_str(metadata)?;
+
+    // Without dependencies, the packages listed are exactly the workspace members
+    let packages = metadata["packages"]
+        .as_array()
+        .ok_or_else(|| Error::CommandFailed("cargo metadata listed no packages".to_string()))?;
+
+    Ok(packages
+        .iter()
+        .filter_map(|package| package["name"].as_str().map(str::to_string))
+        .collect())
+}
+
+/// Get the root directory of the workspace in the current directory, which reported file
+/// names are relative to
+pub fn workspace_root() -> Result<PathBuf, Error> {
+    parse_workspace_root(&cargo_metadata(true)?)
+}
+
+/// Parse the workspace root from the output of `cargo metadata`
+pub fn parse_workspace_root(metadata: &str) -> Result<PathBuf, Error> {
+    let metadata: serde_json::Value = serde_json::from_str(metadata)?;
+
+    metadata["workspace_root"]
+        .as_str()
+        .map(PathBuf::from)
+        .ok_or_else(|| Error::CommandFailed("cargo metadata gave no workspace root".to_string()))
+}
+
+/// Source directory of a local crate: a workspace member or a path dependency
+#[derive(Debug, Clone)]
+struct CrateRoot {
+    name: String,
+    manifest_dir: PathBuf,
+    /// Whether the crate's files are kept in reports
+    reported: bool,
+}
+
+/// Where the sources of the analyzed packages live, to tell which files in a report are theirs
+///
+/// A file belongs to the local crate whose manifest directory most closely encloses it, so a
+/// package nested in another's directory keeps its own files. Files are named relative to the
+/// workspace root, so a file has the same name whichever packages are analyzed.
+#[derive(Debug, Clone)]
+pub struct PackageRoots {
+    crates: Vec<CrateRoot>,
+    /// Directory that file paths are made relative to
+    base: PathBuf,
+}
+
+impl PackageRoots {
+    /// Create from each local crate's name and manifest directory
+    ///
+    /// The files of `packages` are reported, along with those of the `include`d crates whose
+    /// tests are not run.
+    pub fn new(
+        crates: Vec<(String, PathBuf)>,
+        workspace_root: &Path,
+        packages: &[String],
+        include: &[String],
+    ) -> Result<Self, Error> {
+        for crate_name in packages.iter().chain(include) {
+            if !crates.iter().any(|(name, _)| name == crate_name) {
+                return Err(Error::CommandFailed(format!(
+                    "Package '{}' is not a workspace member or path dependency",
+                    crate_name
+                )));
+            }
+        }
+
+        Ok(Self {
+            crates: crates
+                .into_iter()
+                .map(|(name, manifest_dir)| CrateRoot {
+                    reported: packages.contains(&name) || include.contains(&name),
+                    name,
+                    manifest_dir,
+                })
+                .collect(),
+            base: workspace_root.to_path_buf(),
+        })
+    }
+
+    /// Directory that file paths are relative to
+    pub fn base(&self) -> &Path {
+        &self.base
+    }
+
+    /// Names of the crates whose files are reported, sorted
+    pub fn reported_crates(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .crates
+            .iter()
+            .filter(|root| root.reported)
+            .map(|root| root.name.clone())
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Whether the files of more than one crate are reported
+    pub fn spans_several_crates(&self) -> bool {
+        self.crates.iter().filter(|root| root.reported).count() > 1
+    }
+
+    /// Find the local crate whose manifest directory most closely encloses a path
+    fn enclosing_crate(&self, path: &Path) -> Option<&CrateRoot> {
+        self.crates
+            .iter()
+            .filter(|root| path.starts_with(&root.manifest_dir))
+            .max_by_key(|root| root.manifest_dir.components().count())
+    }
+
+    /// Name a source file as it is reported, if it belongs to one of the reported crates
+    pub fn relative_path(&self, path: &Path) -> Option<String> {
+        if !self.enclosing_crate(path)?.reported {
+            return None;
+        }
+
+        // A crate outside the workspace root keeps its absolute paths
+        let Ok(relative) = path.strip_prefix(&self.base) else {
+            return Some(path.to_string_lossy().to_string());
+        };
+        let parts: Vec<String> = relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy().to_string())
+            .collect();
+        Some(parts.join("/"))
+    }
+
+    /// Locate a reported source file on disk
+    pub fn absolute_path(&self, file: &str) -> PathBuf {
+        self.base.join(file)
+    }
+
+    /// Name the crate a reported source file belongs to
+    pub fn crate_name(&self, file: &str) -> Option<&str> {
+        self.enclosing_crate(&self.absolute_path(file))
+            .map(|root| root.name.as_str())
+    }
+}
+
+/// Find the source roots of the packages and included crates of the workspace in the current
+/// directory
+pub fn package_roots(packages: &[String], include: &[String]) -> Result<PackageRoots, Error> {
+    // Path dependencies are only listed along with the rest of the dependency graph
+    parse_package_roots(&cargo_metadata(include.is_empty())?, packages, include)
+}
+
+/// Parse the source roots from the output of `cargo metadata`
+pub fn parse_package_roots(
+    metadata: &str,
+    packages: &[String],
+    include: &[String],
+) -> Result<PackageRoots, Error> {
+    let workspace_root = parse_workspace_root(metadata)?;
+    let metadata: serde_json::Value = serde_json::from_str(metadata)?;
+
+    let crates = metadata["packages"]
+        .as_array()
+        .ok_or_else(|| Error::CommandFailed("cargo metadata listed no packages".to_string()))?
+        .iter()
+        // Only local crates have no source, registry and git dependencies are left out
+        .filter(|package| package["source"].is_null())
+        .filter_map(|package| {
+            let name = package["name"].as_str()?;
+            let manifest_dir = Path::new(package["manifest_path"].as_str()?).parent()?;
+            Some((name.to_string(), manifest_dir.to_path_buf()))
+        })
+        .collect();
+
+    PackageRoots::new(crates, &workspace_root, packages, include)
+}
@@ -9,10 +9,67 @@ pub fn artifacts_dir(output_dir: &Path) -> PathBuf {
         .unwrap_or_else(|| PathBuf::from(".isotarp-artifacts"))
 }
 
+/// Characters escaped within a path segment, which would otherwise split it or be invalid in a
+/// file name on some platform
+const ESCAPED: [(char, &str); 3] = [('%', "%25"), ('/', "%2F"), (':', "%3A")];
+
 /// Converts a test name with '::' separators to a path-friendly format
-/// Example: "module::submodule::test_name" -> "module/submodule/test_name"
+/// Example: "lib:demolib::tests::test_name" -> "lib%3Ademolib/tests/test_name"
+/// A '/' within a segment, as in a doctest's file path, is escaped as "%2F", a ':', which
+/// Windows does not allow in file names, as "%3A", and '%' as "%25"
 pub fn test_name_to_path_segment(test_name: &str) -> String {
-    test_name.replace("::", "/")
+    test_name
+        .split("::")
+        .map(|segment| {
+            let mut escaped = String::with_capacity(segment.len());
+            for c in segment.chars() {
+                match ESCAPED.iter().find(|(special, _)| *special == c) {
+                    Some((_, escape)) => escaped.push_str(escape),
+                    None => escaped.push(c),
+                }
+            }
+            escaped
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Reverses the escaping of a path segment, leaving unknown escapes as they are
+fn unescape_segment(segment: &str) -> String {
+    let mut unescaped = String::with_capacity(segment.len());
+    let mut rest = segment;
+    while let Some(position) = rest.find('%') {
+        unescaped.push_str(&rest[..position]);
+        rest = &rest[position..];
+        match ESCAPED.iter().find(|(_, escape)| {
+            rest.get(..3)
+                .is_some_and(|s| s.eq_ignore_ascii_case(escape))
+        }) {
+            Some((special, escape)) => {
+                unescaped.push(*special);
+                rest = &rest[escape.len()..];
+            }
+            None => {
+                unescaped.push('%');
+                rest = &rest[1..];
+            }
+        }
+    }
+    unescaped.push_str(rest);
+    unescaped
+}
+
+/// Converts a test's directory, relative to the output directory, back to its test name
+/// Example: "lib%3Ademolib/tests/test_name" -> "lib:demolib::tests::test_name"
+pub fn test_name_from_path(path: &Path) -> Option<String> {
+    let segments: Vec<String> = path
+        .components()
+        .map(|component| unescape_segment(&component.as_os_str().to_string_lossy()))
+        .collect();
+    if segments.is_empty() {
+        return None;
+    }
+    Some(segments.join("::"))
 }
 
 /// Constructs a directory path for a specific test within the output directory
@@ -32,3 +89,32 @@ pub fn test_target_dir(output_dir: &Path, test_name: &str) -> PathBuf {
 pub fn test_report_path(output_dir: &Path, test_name: &str) -> PathBuf {
     test_output_dir(output_dir, test_name).join("tarpaulin-report.json")
 }
+
+/// Returns the directory holding cached per-test coverage within the output dir
+pub fn cache_dir(output_dir: &Path) -> PathBuf {
+    output_dir.join("cache")
+}
+
+/// Constructs the cache file path for a specific test
+pub fn test_cache_path(output_dir: &Path, test_name: &str) -> PathBuf {
+    cache_dir(output_dir).join(format!("{}.json", test_name_to_path_segment(test_name)))
+}
+
+/// Constructs the path of the manifest recording the tests completed so far in a run
+pub fn progress_manifest_path(output_dir: &Path) -> PathBuf {
+    output_dir.join("progress.jsonl")
+}
+
+/// Constructs the path of the long-format line table written next to a coverage table
+/// Example: "coverage.csv" -> "coverage-lines.csv"
+pub fn line_table_path(table_path: &Path) -> PathBuf {
+    let stem = table_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let file_name = match table_path.extension() {
+        Some(extension) => format!("{}-lines.{}", stem, extension.to_string_lossy()),
+        None => format!("{}-lines", stem),
+    };
+    table_path.with_file_name(file_name)
+}
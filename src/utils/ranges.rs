@@ -0,0 +1,32 @@
+/// Collapse line numbers into inclusive (start, end) ranges of consecutive lines
+/// Example: [1, 2, 3, 7, 9, 10] -> [(1, 3), (7, 7), (9, 10)]
+pub fn line_ranges(lines: &[u64]) -> Vec<(u64, u64)> {
+    let mut sorted = lines.to_vec();
+    sorted.sort_unstable();
+    sorted.dedup();
+
+    let mut ranges: Vec<(u64, u64)> = Vec::new();
+    for line in sorted {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == line => *end = line,
+            _ => ranges.push((line, line)),
+        }
+    }
+    ranges
+}
+
+/// Format line numbers as a compact, comma-separated list of ranges
+/// Example: [1, 2, 3, 7, 9, 10] -> "1-3, 7, 9-10"
+pub fn format_line_ranges(lines: &[u64]) -> String {
+    line_ranges(lines)
+        .into_iter()
+        .map(|(start, end)| {
+            if start == end {
+                start.to_string()
+            } else {
+                format!("{}-{}", start, end)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,185 @@
+mod common;
+
+use common::collected_with_source;
+use isotarp::affected::{find_affected_tests, parse_diff};
+use isotarp::coverage::analysis::build_analysis;
+use isotarp::types::models::CollectedCoverage;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::Path;
+
+// Source of the src/lib.rs the tests cover
+const SOURCE: &str = "pub fn foo() -> i32 {\n    42\n}\n\npub fn bar() -> &'static str {\n    println!(\"bar\");\n    \"bar\"\n}\n";
+
+const DIFF: &str = "\
+diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -2 +2 @@ pub fn foo() -> i32 {
+-    42
++    43
+@@ -7,0 +8,2 @@ pub fn bar() -> &'static str {
++    println!(\"extra\");
++    println!(\"more\");
+diff --git a/src/new.rs b/src/new.rs
+new file mode 100644
+index 0000000..3333333
+--- /dev/null
++++ b/src/new.rs
+@@ -0,0 +1 @@
++pub fn new() {}
+diff --git a/src/gone.rs b/src/gone.rs
+deleted file mode 100644
+index 4444444..0000000
+--- a/src/gone.rs
++++ /dev/null
+@@ -1,2 +0,0 @@
+--- a line that looks like a header
+-pub fn gone() {}
+";
+
+#[test]
+fn test_parse_diff_old_side_lines() {
+    // Execute
+    let changes = parse_diff(DIFF);
+
+    // Verify
+    assert_eq!(changes.len(), 3);
+    assert_eq!(changes[0].path, "src/lib.rs");
+    assert_eq!(changes[0].lines, BTreeSet::from([2, 7, 8]));
+    assert_eq!(changes[1].path, "src/new.rs");
+    assert!(changes[1].lines.is_empty());
+    assert_eq!(changes[2].path, "src/gone.rs");
+    assert_eq!(changes[2].lines, BTreeSet::from([1, 2]));
+}
+
+#[test]
+fn test_affected_tests_by_line_index() {
+    // Setup test data
+    let collected = collected_with_source(
+        &[
+            ("tests::test_foo", &[1, 2]),
+            ("tests::test_bar", &[6, 7]),
+            ("tests::test_not_bar", &[]),
+        ],
+        SOURCE,
+    );
+    let analysis = build_analysis("demolib", &collected, true);
+    let changes = parse_diff(DIFF);
+
+    // Execute
+    let affected = find_affected_tests(&analysis, &changes, Path::new("/repo"), Path::new("/repo"));
+
+    // Verify
+    assert_eq!(
+        affected.tests,
+        BTreeSet::from(["tests::test_bar".to_string(), "tests::test_foo".to_string()])
+    );
+    assert_eq!(affected.unmapped_files, vec!["src/new.rs", "src/gone.rs"]);
+    assert!(affected.function_level_files.is_empty());
+}
+
+#[test]
+fn test_affected_tests_by_function_without_line_index() {
+    // Setup test data: test_bar covers line 6, but bar's line 8 changed
+    let collected = collected_with_source(
+        &[("tests::test_foo", &[2]), ("tests::test_bar", &[6])],
+        SOURCE,
+    );
+    let analysis = build_analysis("demolib", &collected, false);
+    let changes = parse_diff(
+        "diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -8 +8 @@\n-}\n+ }\n",
+    );
+
+    // Execute
+    let affected = find_affected_tests(&analysis, &changes, Path::new("/repo"), Path::new("/repo"));
+
+    // Verify
+    assert_eq!(
+        affected.tests,
+        BTreeSet::from(["tests::test_bar".to_string()])
+    );
+    assert_eq!(affected.function_level_files, vec!["src/lib.rs"]);
+    assert!(affected.file_level_files.is_empty());
+}
+
+#[test]
+fn test_affected_tests_outside_functions_select_file() {
+    // Setup test data: line 4 lies between foo and bar
+    let collected = collected_with_source(
+        &[
+            ("tests::test_foo", &[2]),
+            ("tests::test_bar", &[6]),
+            ("tests::test_none", &[]),
+        ],
+        SOURCE,
+    );
+    let analysis = build_analysis("demolib", &collected, false);
+    let changes = parse_diff(
+        "diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -4 +4 @@\n-\n+const X: u8 = 1;\n",
+    );
+
+    // Execute
+    let affected = find_affected_tests(&analysis, &changes, Path::new("/repo"), Path::new("/repo"));
+
+    // Verify - every test covering the file is selected
+    assert_eq!(
+        affected.tests,
+        BTreeSet::from(["tests::test_bar".to_string(), "tests::test_foo".to_string()])
+    );
+    assert_eq!(affected.file_level_files, vec!["src/lib.rs"]);
+}
+
+#[test]
+fn test_affected_tests_in_unparsable_file_select_file() {
+    // Setup test data
+    let collected = collected_with_source(
+        &[("tests::test_foo", &[2]), ("tests::test_bar", &[3])],
+        "fn broken( {\n    1\n    2\n}\n",
+    );
+    let analysis = build_analysis("demolib", &collected, false);
+    let changes = parse_diff(
+        "diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -2 +2 @@\n-    1\n+    0\n",
+    );
+
+    // Execute
+    let affected = find_affected_tests(&analysis, &changes, Path::new("/repo"), Path::new("/repo"));
+
+    // Verify
+    assert_eq!(
+        affected.tests,
+        BTreeSet::from(["tests::test_bar".to_string(), "tests::test_foo".to_string()])
+    );
+    assert_eq!(affected.file_level_files, vec!["src/lib.rs"]);
+}
+
+#[test]
+fn test_affected_tests_ignore_sibling_crates() {
+    // Setup test data: a workspace in a subdirectory of the repository, where a sibling crate
+    // has a file with the same path within its crate
+    let mut collected = CollectedCoverage::default();
+    collected.tests.insert(
+        "lib:app::tests::test_run".to_string(),
+        HashMap::from([("app/src/lib.rs".to_string(), HashSet::from([2]))]),
+    );
+    let analysis = build_analysis("app", &collected, true);
+    let changes = parse_diff(
+        "diff --git a/rust/core/src/lib.rs b/rust/core/src/lib.rs\n--- a/rust/core/src/lib.rs\n+++ b/rust/core/src/lib.rs\n@@ -2 +2 @@\n-a\n+b\n\
+         diff --git a/rust/app/src/lib.rs b/rust/app/src/lib.rs\n--- a/rust/app/src/lib.rs\n+++ b/rust/app/src/lib.rs\n@@ -2 +2 @@\n-a\n+b\n",
+    );
+
+    // Execute
+    let affected = find_affected_tests(
+        &analysis,
+        &changes,
+        Path::new("/repo"),
+        Path::new("/repo/rust"),
+    );
+
+    // Verify - only the change to the analyzed crate's file selects the test
+    assert_eq!(
+        affected.tests,
+        BTreeSet::from(["lib:app::tests::test_run".to_string()])
+    );
+    assert_eq!(affected.unmapped_files, vec!["rust/core/src/lib.rs"]);
+}
@@ -0,0 +1,319 @@
+mod common;
+
+use common::coverage;
+use isotarp::coverage::analysis::{analyze_test_coverage, build_analysis, find_subsuming_tests};
+use isotarp::types::models::{CollectedCoverage, DominantTest, LineHits};
+use std::collections::{BTreeMap, HashMap};
+
+#[test]
+fn test_subsumed_by_strict_superset() {
+    // Setup test data
+    let mut results = HashMap::new();
+    results.insert(
+        "tests::test_big".to_string(),
+        coverage(&[("src/lib.rs", &[1, 2, 3]), ("src/functions.rs", &[7])]),
+    );
+    results.insert(
+        "tests::test_small".to_string(),
+        coverage(&[("src/lib.rs", &[2, 3])]),
+    );
+
+    // Execute
+    let subsumptions = find_subsuming_tests(&results);
+
+    // Verify
+    let small = &subsumptions["tests::test_small"];
+    assert_eq!(small.len(), 1);
+    assert_eq!(small[0].test, "tests::test_big");
+    assert!(!small[0].equal);
+    assert!(!subsumptions.contains_key("tests::test_big"));
+}
+
+#[test]
+fn test_subsumed_by_identical_coverage() {
+    // Setup test data
+    let mut results = HashMap::new();
+    results.insert(
+        "tests::test_a".to_string(),
+        coverage(&[("src/lib.rs", &[1, 2])]),
+    );
+    results.insert(
+        "tests::test_b".to_string(),
+        coverage(&[("src/lib.rs", &[1, 2])]),
+    );
+
+    // Execute
+    let subsumptions = find_subsuming_tests(&results);
+
+    // Verify - each test subsumes the other
+    assert_eq!(subsumptions["tests::test_a"][0].test, "tests::test_b");
+    assert!(subsumptions["tests::test_a"][0].equal);
+    assert_eq!(subsumptions["tests::test_b"][0].test, "tests::test_a");
+    assert!(subsumptions["tests::test_b"][0].equal);
+}
+
+#[test]
+fn test_subsumed_by_skips_overlapping_and_empty() {
+    // Setup test data
+    let mut results = HashMap::new();
+    results.insert(
+        "tests::test_left".to_string(),
+        coverage(&[("src/lib.rs", &[1, 2])]),
+    );
+    results.insert(
+        "tests::test_right".to_string(),
+        coverage(&[("src/lib.rs", &[2, 3])]),
+    );
+    results.insert("tests::test_not_bar".to_string(), HashMap::new());
+
+    // Execute
+    let analysis = analyze_test_coverage(&results);
+
+    // Verify
+    assert!(analysis["tests::test_left"].subsumed_by.is_empty());
+    assert!(analysis["tests::test_right"].subsumed_by.is_empty());
+    assert!(analysis["tests::test_not_bar"].subsumed_by.is_empty());
+}
+
+#[test]
+fn test_line_index_lists_covering_tests() {
+    // Setup test data
+    let mut results = HashMap::new();
+    results.insert(
+        "tests::test_foo".to_string(),
+        coverage(&[("src/lib.rs", &[1, 2]), ("src/functions.rs", &[5])]),
+    );
+    results.insert(
+        "tests::test_bar".to_string(),
+        coverage(&[("src/lib.rs", &[2, 3])]),
+    );
+
+    // Execute
+    let collected = CollectedCoverage {
+        tests: results,
+        ..Default::default()
+    };
+    let analysis = build_analysis("demolib", &collected, true);
+
+    // Verify
+    let lib = &analysis.files["src/lib.rs"];
+    assert_eq!(lib.covered_lines, 3);
+    let lines = lib.lines.as_ref().expect("line index requested");
+    assert_eq!(lines[&1], vec!["tests::test_foo".to_string()]);
+    assert_eq!(
+        lines[&2],
+        vec!["tests::test_bar".to_string(), "tests::test_foo".to_string()]
+    );
+    assert_eq!(lines[&3], vec!["tests::test_bar".to_string()]);
+    assert_eq!(analysis.files["src/functions.rs"].covered_lines, 1);
+}
+
+#[test]
+fn test_line_index_omitted_by_default() {
+    // Setup test data
+    let mut results = HashMap::new();
+    results.insert(
+        "tests::test_foo".to_string(),
+        coverage(&[("src/lib.rs", &[1, 2])]),
+    );
+
+    // Execute
+    let collected = CollectedCoverage {
+        tests: results,
+        ..Default::default()
+    };
+    let analysis = build_analysis("demolib", &collected, false);
+
+    // Verify
+    assert_eq!(analysis.files["src/lib.rs"].covered_lines, 2);
+    assert!(analysis.files["src/lib.rs"].lines.is_none());
+}
+
+#[test]
+fn test_uncovered_lines_per_file_and_overall() {
+    // Setup test data: functions.rs is coverable but no test reaches it
+    let mut results = HashMap::new();
+    results.insert(
+        "tests::test_foo".to_string(),
+        coverage(&[("src/lib.rs", &[1, 2])]),
+    );
+    results.insert("tests::test_not_bar".to_string(), HashMap::new());
+    let collected = CollectedCoverage {
+        tests: results,
+        coverable: coverage(&[("src/lib.rs", &[1, 2, 3, 4]), ("src/functions.rs", &[6, 7])]),
+        ..Default::default()
+    };
+
+    // Execute
+    let analysis = build_analysis("demolib", &collected, false);
+
+    // Verify
+    let lib = &analysis.files["src/lib.rs"];
+    assert_eq!(lib.coverable_lines, 4);
+    assert_eq!(lib.covered_lines, 2);
+    assert_eq!(lib.uncovered_lines, vec![3, 4]);
+
+    let functions = &analysis.files["src/functions.rs"];
+    assert_eq!(functions.coverable_lines, 2);
+    assert_eq!(functions.covered_lines, 0);
+    assert_eq!(functions.uncovered_lines, vec![6, 7]);
+
+    assert_eq!(analysis.totals.coverable_lines, 6);
+    assert_eq!(analysis.totals.covered_lines, 2);
+    assert_eq!(analysis.totals.uncovered_lines, 4);
+}
+
+// Helper to build a test's hit counts from (file, [(line, hits)]) pairs
+fn hits(files: &[(&str, &[(u64, u64)])]) -> LineHits {
+    files
+        .iter()
+        .map(|(file, lines)| (file.to_string(), lines.iter().copied().collect()))
+        .collect()
+}
+
+#[test]
+fn test_hit_counts_and_dominant_tests() {
+    // Setup test data: both tests share lines 1 and 2, test_hot loops over line 2
+    let mut collected = CollectedCoverage::default();
+    collected.tests.insert(
+        "tests::test_hot".to_string(),
+        coverage(&[("src/lib.rs", &[1, 2])]),
+    );
+    collected.tests.insert(
+        "tests::test_cold".to_string(),
+        coverage(&[("src/lib.rs", &[1, 2, 3])]),
+    );
+    collected.hits.insert(
+        "tests::test_hot".to_string(),
+        hits(&[("src/lib.rs", &[(1, 1), (2, 50)])]),
+    );
+    collected.hits.insert(
+        "tests::test_cold".to_string(),
+        hits(&[("src/lib.rs", &[(1, 1), (2, 1), (3, 1)])]),
+    );
+
+    // Execute
+    let analysis = build_analysis("demolib", &collected, true);
+
+    // Verify
+    let hot = &analysis.tests["tests::test_hot"];
+    assert_eq!(hot.total_hits, 51);
+    assert_eq!(hot.dominant_lines, 1);
+    assert_eq!(hot.files["src/lib.rs"].line_hits.as_ref().unwrap()[&2], 50);
+
+    let cold = &analysis.tests["tests::test_cold"];
+    assert_eq!(cold.total_hits, 3);
+    assert_eq!(cold.dominant_lines, 0);
+
+    // Line 1 is a tie and line 3 is not shared, so only line 2 has a dominant test
+    let dominant = analysis.files["src/lib.rs"].dominant.as_ref().unwrap();
+    assert_eq!(dominant.len(), 1);
+    assert_eq!(
+        dominant[&2],
+        DominantTest {
+            test: "tests::test_hot".to_string(),
+            hits: 50,
+            total_hits: 51,
+        }
+    );
+}
+
+#[test]
+fn test_uniqueness_per_crate() {
+    // Setup test data: a test of app reaches core lines that core's own test misses
+    let mut results = HashMap::new();
+    results.insert(
+        "app/lib:app::tests::test_run".to_string(),
+        coverage(&[("app/src/lib.rs", &[1]), ("core/src/lib.rs", &[1, 2, 3])]),
+    );
+    results.insert(
+        "core/lib:core::tests::test_parse".to_string(),
+        coverage(&[("core/src/lib.rs", &[1])]),
+    );
+    let collected = CollectedCoverage {
+        tests: results,
+        coverable: coverage(&[("app/src/lib.rs", &[1]), ("core/src/lib.rs", &[1, 2, 3, 4])]),
+        crates: HashMap::from([
+            ("app/src/lib.rs".to_string(), "app".to_string()),
+            ("core/src/lib.rs".to_string(), "core".to_string()),
+        ]),
+        ..Default::default()
+    };
+
+    // Execute
+    let analysis = build_analysis("app,core", &collected, false);
+
+    // Verify
+    let core = &analysis.crates["core"];
+    assert_eq!(core.coverable_lines, 4);
+    assert_eq!(core.covered_lines, 3);
+    assert_eq!(
+        core.missed_by_own_tests.as_ref().unwrap()["core/src/lib.rs"],
+        vec![2, 3]
+    );
+    let outside = &core.tests["app/lib:app::tests::test_run"];
+    assert_eq!(outside.covered_lines, 3);
+    assert_eq!(outside.unique_lines, 2);
+    assert_eq!(outside.missed_by_own_tests, Some(2));
+    assert_eq!(
+        core.tests["core/lib:core::tests::test_parse"].missed_by_own_tests,
+        Some(0)
+    );
+    assert_eq!(
+        analysis.crates["app"].missed_by_own_tests,
+        Some(BTreeMap::new())
+    );
+}
+
+#[test]
+fn test_uniqueness_per_included_crate() {
+    // Setup test data: core is only included, so none of its own tests ran
+    let mut results = HashMap::new();
+    results.insert(
+        "app/lib:app::tests::test_run".to_string(),
+        coverage(&[("app/src/lib.rs", &[1]), ("core/src/lib.rs", &[1, 2])]),
+    );
+    let collected = CollectedCoverage {
+        tests: results,
+        crates: HashMap::from([
+            ("app/src/lib.rs".to_string(), "app".to_string()),
+            ("core/src/lib.rs".to_string(), "core".to_string()),
+        ]),
+        ..Default::default()
+    };
+
+    // Execute
+    let analysis = build_analysis("app", &collected, false);
+
+    // Verify - lines missed by core's own tests are unknown rather than all of them
+    let core = &analysis.crates["core"];
+    assert_eq!(core.covered_lines, 2);
+    assert_eq!(core.missed_by_own_tests, None);
+    assert_eq!(
+        core.tests["app/lib:app::tests::test_run"].missed_by_own_tests,
+        None
+    );
+    assert!(analysis.crates["app"].missed_by_own_tests.is_some());
+    let json = serde_json::to_value(&analysis.crates["core"]).unwrap();
+    assert!(json.get("missed_by_own_tests").is_none());
+}
+
+#[test]
+fn test_crates_omitted_for_a_single_crate() {
+    // Setup test data
+    let mut results = HashMap::new();
+    results.insert(
+        "tests::test_foo".to_string(),
+        coverage(&[("src/lib.rs", &[1])]),
+    );
+    let collected = CollectedCoverage {
+        tests: results,
+        ..Default::default()
+    };
+
+    // Execute
+    let analysis = build_analysis("demolib", &collected, false);
+
+    // Verify
+    assert!(analysis.crates.is_empty());
+}
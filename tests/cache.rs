@@ -0,0 +1,111 @@
+use isotarp::coverage::cache::{CACHE_VERSION, CoverageCache};
+use isotarp::types::models::TestCoverage;
+use rstest::*;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use temp_testdir::TempDir;
+
+// Helper to build a test's coverage of the given lines of a single source file
+fn coverage(file: &Path, lines: &[u64]) -> TestCoverage {
+    let file = file.to_string_lossy().to_string();
+    let lines: HashSet<u64> = lines.iter().copied().collect();
+    TestCoverage {
+        covered: HashMap::from([(file.clone(), lines.clone())]),
+        coverable: HashMap::from([(file, lines)]),
+        hits: HashMap::new(),
+    }
+}
+
+// Helper to open a cache where each test lives in a binary with the given hash
+fn cache(output_dir: &Path, binaries: &[(&str, &str)]) -> CoverageCache {
+    let binary_hashes = binaries
+        .iter()
+        .map(|(test_name, hash)| (test_name.to_string(), hash.to_string()))
+        .collect();
+    CoverageCache::new(output_dir, binary_hashes)
+}
+
+#[test]
+fn test_cache_reuses_unchanged_test() {
+    // Setup test data
+    let temp_dir = TempDir::default();
+    let source = temp_dir.join("lib.rs");
+    fs::write(&source, "pub fn foo() {}\n").unwrap();
+    let stored = coverage(&source, &[1]);
+    cache(&temp_dir, &[("tests::test_foo", "abc")])
+        .store("tests::test_foo", &stored)
+        .unwrap();
+
+    // Execute
+    let cached = cache(&temp_dir, &[("tests::test_foo", "abc")]).lookup("tests::test_foo");
+
+    // Verify
+    assert_eq!(cached.unwrap().covered, stored.covered);
+}
+
+#[test]
+fn test_cache_invalidated_by_binary_or_source_change() {
+    // Setup test data
+    let temp_dir = TempDir::default();
+    let source = temp_dir.join("lib.rs");
+    fs::write(&source, "pub fn foo() {}\n").unwrap();
+    cache(&temp_dir, &[("tests::test_foo", "abc")])
+        .store("tests::test_foo", &coverage(&source, &[1]))
+        .unwrap();
+
+    // Execute & Verify - a rebuilt test binary invalidates the entry
+    let mut rebuilt = cache(&temp_dir, &[("tests::test_foo", "def")]);
+    assert!(rebuilt.lookup("tests::test_foo").is_none());
+
+    // Execute & Verify - so does an edited source file
+    fs::write(&source, "pub fn foo() { println!(\"changed\"); }\n").unwrap();
+    let mut edited = cache(&temp_dir, &[("tests::test_foo", "abc")]);
+    assert!(edited.lookup("tests::test_foo").is_none());
+}
+
+#[test]
+fn test_cache_skips_tests_outside_known_binaries() {
+    // Setup test data
+    let temp_dir = TempDir::default();
+    let source = temp_dir.join("lib.rs");
+    fs::write(&source, "pub fn foo() {}\n").unwrap();
+    let mut cache = cache(&temp_dir, &[]);
+
+    // Execute
+    cache
+        .store("tests::test_foo", &coverage(&source, &[1]))
+        .unwrap();
+
+    // Verify
+    assert!(cache.lookup("tests::test_foo").is_none());
+    assert!(!temp_dir.join("cache").exists());
+}
+
+#[rstest]
+#[case::unversioned(None)]
+#[case::newer(Some(CACHE_VERSION + 1))]
+fn test_cache_ignores_entries_from_other_versions(#[case] version: Option<u32>) {
+    // Setup test data - an entry stored by a build that collected coverage differently
+    let temp_dir = TempDir::default();
+    let source = temp_dir.join("lib.rs");
+    fs::write(&source, "pub fn foo() {}\n").unwrap();
+    cache(&temp_dir, &[("tests::test_foo", "abc")])
+        .store("tests::test_foo", &coverage(&source, &[1]))
+        .unwrap();
+    let entry_path = temp_dir.join("cache").join("tests").join("test_foo.json");
+    let mut entry: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&entry_path).unwrap()).unwrap();
+    let fields = entry.as_object_mut().unwrap();
+    match version {
+        Some(version) => fields.insert("version".to_string(), version.into()),
+        None => fields.remove("version"),
+    };
+    fs::write(&entry_path, entry.to_string()).unwrap();
+
+    // Execute
+    let cached = cache(&temp_dir, &[("tests::test_foo", "abc")]).lookup("tests::test_foo");
+
+    // Verify
+    assert!(cached.is_none());
+}
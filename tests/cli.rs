@@ -1,4 +1,4 @@
-use isotarp::resolve::resolve_test_patterns;
+use isotarp::resolve::{find_prefix_collisions, resolve_test_patterns};
 
 #[test]
 fn test_exact_match() {
@@ -191,3 +191,67 @@ fn test_question_mark_wildcard() {
     assert!(!matched.contains(&"tests::test_food".to_string()));
     assert!(unmatched.is_empty());
 }
+
+#[test]
+fn test_find_prefix_collisions() {
+    // Setup test data
+    let available_tests = vec![
+        "tests::test_foo_bar".to_string(),
+        "tests::test_foo".to_string(),
+        "tests::test_food".to_string(),
+        "tests::test_fo".to_string(),
+        "tests::test_other".to_string(),
+    ];
+    let selected = vec![
+        "tests::test_foo".to_string(),
+        "tests::test_other".to_string(),
+    ];
+
+    // Execute
+    let collisions = find_prefix_collisions(&available_tests, &selected);
+
+    // Verify - only selected tests are reported, with every test they prefix
+    assert_eq!(
+        collisions,
+        vec![(
+            "tests::test_foo".to_string(),
+            vec![
+                "tests::test_foo_bar".to_string(),
+                "tests::test_food".to_string()
+            ]
+        )]
+    );
+}
+
+#[test]
+fn test_qualified_tests_match_by_path() {
+    // Setup test data - the same test path in the lib and in an integration test
+    let available_tests = vec![
+        "lib:demolib::tests::it_works".to_string(),
+        "test:integration::tests::it_works".to_string(),
+        "test:integration::tests::other".to_string(),
+    ];
+
+    // Execute
+    let (by_path, _) = resolve_test_patterns(&available_tests, &["tests::it_works".to_string()]);
+    let (qualified, _) = resolve_test_patterns(
+        &available_tests,
+        &["test:integration::tests::it_works".to_string()],
+    );
+    let (by_target, _) =
+        resolve_test_patterns(&available_tests, &["test:integration::*".to_string()]);
+
+    // Verify
+    assert_eq!(
+        by_path,
+        vec![
+            "lib:demolib::tests::it_works".to_string(),
+            "test:integration::tests::it_works".to_string()
+        ]
+    );
+    assert_eq!(
+        qualified,
+        vec!["test:integration::tests::it_works".to_string()]
+    );
+    assert_eq!(by_target.len(), 2);
+}
@@ -0,0 +1,76 @@
+use isotarp::coverage::analysis::build_analysis;
+use isotarp::export::cobertura::render_cobertura;
+use isotarp::types::models::CollectedCoverage;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+// Helper to collect coverage from (test, file, lines) entries
+fn collected(entries: &[(&str, &str, &[u64])]) -> CollectedCoverage {
+    let mut collected = CollectedCoverage::default();
+    for (test_name, file, lines) in entries {
+        collected
+            .tests
+            .entry(test_name.to_string())
+            .or_insert_with(HashMap::new)
+            .insert(
+                file.to_string(),
+                lines.iter().copied().collect::<HashSet<u64>>(),
+            );
+    }
+    collected
+        .coverable
+        .insert("demolib/src/lib.rs".to_string(), (1..=4).collect());
+    collected
+        .coverable
+        .insert("demolib/src/util/mod.rs".to_string(), (1..=2).collect());
+    collected
+}
+
+#[test]
+fn test_render_cobertura_counts_covering_tests() {
+    // Setup test data
+    let coverage = collected(&[
+        ("tests::test_a", "demolib/src/lib.rs", &[1, 2]),
+        ("tests::test_b", "demolib/src/lib.rs", &[2, 3]),
+    ]);
+    let analysis = build_analysis("demolib", &coverage, false);
+
+    // Execute
+    let xml = render_cobertura(&coverage, &analysis, Path::new("/work"));
+
+    // Verify
+    assert!(xml.contains("<coverage lines-valid=\"6\" lines-covered=\"3\" line-rate=\"0.5000\""));
+    assert!(xml.contains("<source>/work</source>"));
+    assert!(xml.contains("<package name=\"demolib.src\" line-rate=\"0.7500\""));
+    assert!(xml.contains("<package name=\"demolib.src.util\" line-rate=\"0.0000\""));
+    assert!(
+        xml.contains("<class name=\"lib.rs\" filename=\"demolib/src/lib.rs\" line-rate=\"0.7500\"")
+    );
+    assert!(
+        xml.contains("<line number=\"1\" hits=\"1\" branch=\"false\" tests=\"tests::test_a\"/>")
+    );
+    assert!(xml.contains(
+        "<line number=\"2\" hits=\"2\" branch=\"false\" tests=\"tests::test_a tests::test_b\"/>"
+    ));
+    assert!(xml.contains("<line number=\"4\" hits=\"0\" branch=\"false\"/>"));
+    assert!(xml.trim_end().ends_with("</coverage>"));
+}
+
+#[test]
+fn test_render_cobertura_methods() {
+    // Setup test data
+    let mut coverage = collected(&[("tests::test_a", "demolib/src/lib.rs", &[2])]);
+    coverage.sources.insert(
+        "demolib/src/lib.rs".to_string(),
+        "pub fn first() {\n    let _ = 1;\n}\npub fn second() {}\n".to_string(),
+    );
+    let analysis = build_analysis("demolib", &coverage, false);
+
+    // Execute
+    let xml = render_cobertura(&coverage, &analysis, Path::new("/work"));
+
+    // Verify
+    assert!(xml.contains("filename=\"demolib/src/lib.rs\""));
+    assert!(xml.contains("<method name=\"first\" signature=\"\" line-rate=\"0.3333\""));
+    assert!(xml.contains("<method name=\"second\" signature=\"\" line-rate=\"0.0000\""));
+}
@@ -1,7 +1,8 @@
 #![allow(clippy::too_many_arguments)]
 // tests/commands.rs
-use isotarp::cli::execute_analyze_command;
-use isotarp::types::models::TargetMode;
+use clap::Parser;
+use isotarp::cli::{AnalyzeOptions, Cli, execute_analyze_command};
+use isotarp::types::models::CollectOptions;
 use rstest::*;
 use std::{env, fs, path::Path, path::PathBuf};
 use temp_testdir::TempDir;
@@ -102,11 +103,12 @@ fn test_execute_analyze_command(
 
     // Execute the analyze command
     let result = execute_analyze_command(
-        "demolib",
+        &["demolib".to_string()],
         tests.clone(),
         &output_location,
         &report_path,
-        TargetMode::default(),
+        CollectOptions::default(),
+        AnalyzeOptions::default(),
     );
 
     // Restore the original directory - use current_dir captured right before the test
@@ -168,7 +170,7 @@ fn test_execute_list_command(
     println!("Current dir: {:?}", env::current_dir().unwrap());
 
     // Execute the list command
-    let result = execute_list_command(package);
+    let result = execute_list_command(&[package.to_string()], false);
 
     // Restore the original directory
     env::set_current_dir(original_dir).unwrap();
@@ -201,3 +203,21 @@ fn test_execute_list_command(
         }
     }
 }
+
+#[rstest]
+#[case::lcov("--lcov")]
+#[case::html("--html")]
+#[case::cobertura("--cobertura")]
+#[case::markdown("--markdown")]
+#[case::csv("--csv")]
+#[case::tsv("--tsv")]
+fn test_analyze_shard_conflicts_with_exports(#[case] export: &str) {
+    // Execute
+    let result = Cli::try_parse_from(["isotarp", "analyze", "--shard", "1/2", export, "out"]);
+
+    // Verify
+    assert_eq!(
+        result.err().map(|e| e.kind()),
+        Some(clap::error::ErrorKind::ArgumentConflict)
+    );
+}
@@ -0,0 +1,60 @@
+// Helpers shared by the integration tests, each of which uses only some of them
+#![allow(dead_code)]
+
+use isotarp::IsotarpAnalysis;
+use isotarp::coverage::analysis::build_analysis;
+use isotarp::types::models::CollectedCoverage;
+use std::collections::{HashMap, HashSet};
+
+// Helper to build a test's coverage from (file, lines) pairs
+pub fn coverage(files: &[(&str, &[u64])]) -> HashMap<String, HashSet<u64>> {
+    files
+        .iter()
+        .map(|(file, lines)| (file.to_string(), lines.iter().copied().collect()))
+        .collect()
+}
+
+// Helper to collect coverage where each test hits the given lines of src/lib.rs once,
+// out of coverable lines 1 to 6
+pub fn collected(tests: &[(&str, &[u64])]) -> CollectedCoverage {
+    let mut collected = CollectedCoverage::default();
+    for (test_name, lines) in tests {
+        collected.tests.insert(
+            test_name.to_string(),
+            HashMap::from([(
+                "src/lib.rs".to_string(),
+                lines.iter().copied().collect::<HashSet<u64>>(),
+            )]),
+        );
+        collected.hits.insert(
+            test_name.to_string(),
+            HashMap::from([(
+                "src/lib.rs".to_string(),
+                lines.iter().map(|line| (*line, 1)).collect(),
+            )]),
+        );
+    }
+    collected
+        .coverable
+        .insert("src/lib.rs".to_string(), (1..=6).collect());
+    collected
+}
+
+// Helper to build an analysis of package "demolib" from the coverage of `collected`
+pub fn analysis(tests: &[(&str, &[u64])], line_index: bool) -> IsotarpAnalysis {
+    build_analysis("demolib", &collected(tests), line_index)
+}
+
+// Helper to collect coverage as `collected` does, from a src/lib.rs with the given source
+// whose every line is coverable
+pub fn collected_with_source(tests: &[(&str, &[u64])], source: &str) -> CollectedCoverage {
+    let mut collected = collected(tests);
+    collected.coverable.insert(
+        "src/lib.rs".to_string(),
+        (1..=source.lines().count() as u64).collect(),
+    );
+    collected
+        .sources
+        .insert("src/lib.rs".to_string(), source.to_string());
+    collected
+}
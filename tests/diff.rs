@@ -0,0 +1,90 @@
+mod common;
+
+use common::analysis;
+use isotarp::diff::diff_analyses;
+use std::collections::BTreeMap;
+
+#[test]
+fn test_diff_added_removed_and_changed_tests() {
+    // Setup test data
+    let old = analysis(
+        &[
+            ("tests::test_a", &[1, 2]),
+            ("tests::test_b", &[2, 3]),
+            ("tests::test_c", &[4]),
+        ],
+        false,
+    );
+    let new = analysis(
+        &[
+            ("tests::test_a", &[1, 2]),
+            ("tests::test_b", &[2, 3, 4]),
+            ("tests::test_d", &[5]),
+        ],
+        false,
+    );
+
+    // Execute
+    let diff = diff_analyses(&old, &new);
+
+    // Verify
+    assert_eq!(diff.added_tests, vec!["tests::test_d"]);
+    assert_eq!(diff.removed_tests, vec!["tests::test_c"]);
+    assert_eq!(diff.changed_tests.len(), 1);
+    let change = &diff.changed_tests[0];
+    assert_eq!(change.test, "tests::test_b");
+    assert_eq!(change.old_total_covered_lines, 2);
+    assert_eq!(change.new_total_covered_lines, 3);
+    assert_eq!(change.old_unique_covered_lines, 1);
+    assert_eq!(change.new_unique_covered_lines, 2);
+    // Line 4 moved from test_c to test_b, so no line lost its coverage
+    assert!(diff.lost_lines.is_empty());
+}
+
+#[test]
+fn test_diff_lost_lines_with_line_index() {
+    // Setup test data - lines 2 and 3 are shared and lose both their tests
+    let old = analysis(
+        &[
+            ("tests::test_a", &[1, 2, 3]),
+            ("tests::test_b", &[2, 3]),
+            ("tests::test_c", &[4]),
+        ],
+        true,
+    );
+    let new = analysis(&[("tests::test_a", &[1]), ("tests::test_c", &[4])], true);
+
+    // Execute
+    let diff = diff_analyses(&old, &new);
+
+    // Verify
+    let expected = BTreeMap::from([
+        (
+            2,
+            vec!["tests::test_a".to_string(), "tests::test_b".to_string()],
+        ),
+        (
+            3,
+            vec!["tests::test_a".to_string(), "tests::test_b".to_string()],
+        ),
+    ]);
+    assert_eq!(diff.lost_lines["src/lib.rs"], expected);
+}
+
+#[test]
+fn test_diff_lost_lines_without_line_index() {
+    // Setup test data - only unique lines can be attributed without an index
+    let old = analysis(
+        &[("tests::test_a", &[1, 2]), ("tests::test_b", &[2, 5])],
+        false,
+    );
+    let new = analysis(&[("tests::test_a", &[1, 2])], false);
+
+    // Execute
+    let diff = diff_analyses(&old, &new);
+
+    // Verify
+    assert_eq!(diff.removed_tests, vec!["tests::test_b"]);
+    let expected = BTreeMap::from([(5, vec!["tests::test_b".to_string()])]);
+    assert_eq!(diff.lost_lines["src/lib.rs"], expected);
+}
@@ -0,0 +1,143 @@
+use isotarp::coverage::analysis::build_analysis;
+use isotarp::coverage::functions::{FunctionSpan, enclosing_function, find_function_spans};
+use isotarp::types::models::CollectedCoverage;
+use std::collections::{HashMap, HashSet};
+
+const SOURCE: &str = r#"pub fn foo() -> i32 {
+    42
+}
+
+pub struct Parser;
+
+impl Parser {
+    pub fn parse_expr(&self) -> i32 {
+        fn helper() -> i32 {
+            1
+        }
+        helper()
+    }
+}
+
+impl std::fmt::Display for Parser {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "parser")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_foo() {
+        assert_eq!(super::foo(), 42);
+    }
+}
+"#;
+
+// Helper to look up a span by name
+fn span<'a>(spans: &'a [FunctionSpan], name: &str) -> &'a FunctionSpan {
+    spans
+        .iter()
+        .find(|span| span.name == name)
+        .unwrap_or_else(|| panic!("no span named {}", name))
+}
+
+#[test]
+fn test_find_function_spans_names_and_lines() {
+    // Execute
+    let spans = find_function_spans(SOURCE).unwrap();
+
+    // Verify
+    assert_eq!(
+        (span(&spans, "foo").start_line, span(&spans, "foo").end_line),
+        (1, 3)
+    );
+    assert_eq!(span(&spans, "Parser::parse_expr").start_line, 8);
+    assert_eq!(span(&spans, "Parser::parse_expr").end_line, 13);
+    assert_eq!(span(&spans, "Parser::parse_expr::helper").start_line, 9);
+    assert_eq!(span(&spans, "<Parser as Display>::fmt").start_line, 17);
+    assert_eq!(span(&spans, "tests::test_foo").start_line, 25);
+}
+
+#[test]
+fn test_enclosing_function_prefers_innermost() {
+    // Setup test data
+    let spans = find_function_spans(SOURCE).unwrap();
+
+    // Execute & verify
+    assert_eq!(
+        enclosing_function(&spans, 10).unwrap().name,
+        "Parser::parse_expr::helper"
+    );
+    assert_eq!(
+        enclosing_function(&spans, 12).unwrap().name,
+        "Parser::parse_expr"
+    );
+    assert!(enclosing_function(&spans, 5).is_none());
+}
+
+#[test]
+fn test_find_function_spans_rejects_invalid_source() {
+    assert!(find_function_spans("fn broken( {").is_err());
+}
+
+#[test]
+fn test_function_attribution_in_analysis() {
+    // Setup test data: test_foo reaches foo and parse_expr, test_parse only parse_expr
+    let mut collected = CollectedCoverage::default();
+    collected.tests.insert(
+        "tests::test_foo".to_string(),
+        HashMap::from([("src/lib.rs".to_string(), HashSet::from([2, 12]))]),
+    );
+    collected.tests.insert(
+        "tests::test_parse".to_string(),
+        HashMap::from([("src/lib.rs".to_string(), HashSet::from([10, 12]))]),
+    );
+    collected
+        .coverable
+        .insert("src/lib.rs".to_string(), HashSet::from([2, 10, 12, 18]));
+    collected
+        .sources
+        .insert("src/lib.rs".to_string(), SOURCE.to_string());
+
+    // Execute
+    let analysis = build_analysis("demolib", &collected, false);
+
+    // Verify per-test attribution
+    let foo = &analysis.tests["tests::test_foo"];
+    let foo_functions = &foo.files["src/lib.rs"].functions;
+    assert!(foo_functions["foo"].only_covering_test);
+    assert_eq!(foo_functions["foo"].unique_covered_lines, 1);
+    assert!(!foo_functions["Parser::parse_expr"].only_covering_test);
+    assert_eq!(foo.unique_functions, 1);
+
+    let parse = &analysis.tests["tests::test_parse"];
+    assert!(parse.files["src/lib.rs"].functions["Parser::parse_expr::helper"].only_covering_test);
+
+    // Verify per-file summary
+    let functions = &analysis.files["src/lib.rs"].functions;
+    assert_eq!(
+        functions["Parser::parse_expr"].tests,
+        vec![
+            "tests::test_foo".to_string(),
+            "tests::test_parse".to_string()
+        ]
+    );
+    let fmt = &functions["<Parser as Display>::fmt"];
+    assert_eq!(fmt.coverable_lines, 1);
+    assert_eq!(fmt.covered_lines, 0);
+    assert!(fmt.tests.is_empty());
+}
+
+#[test]
+fn test_find_function_spans_tells_apart_duplicate_names() {
+    // Setup test data
+    let source = "struct Wrapper<T>(T);\n\nimpl Wrapper<u8> {\n    fn new() -> Self {\n        Wrapper(0)\n    }\n}\n\nimpl Wrapper<u16> {\n    fn new() -> Self {\n        Wrapper(0)\n    }\n}\n";
+
+    // Execute
+    let spans = find_function_spans(source).unwrap();
+
+    // Verify
+    assert_eq!(span(&spans, "Wrapper::new (line 4)").end_line, 6);
+    assert_eq!(span(&spans, "Wrapper::new (line 10)").end_line, 12);
+    assert!(spans.iter().all(|span| span.name != "Wrapper::new"));
+}
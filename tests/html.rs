@@ -0,0 +1,47 @@
+mod common;
+
+use common::collected_with_source;
+use isotarp::coverage::analysis::build_analysis;
+use isotarp::export::html::render_html;
+
+// Source of the src/lib.rs the tests cover
+const SOURCE: &str = "pub fn lt(a: u8) -> bool {\n    a < 3\n}\nfn unused() {}\n";
+
+#[test]
+fn test_render_html_annotates_lines() {
+    // Setup test data
+    let coverage = collected_with_source(
+        &[("tests::test_a", &[1, 2]), ("tests::test_b", &[2, 3])],
+        SOURCE,
+    );
+    let analysis = build_analysis("demolib", &coverage, false);
+
+    // Execute
+    let html = render_html(&coverage, &analysis);
+
+    // Verify - line 2 is shared, line 1 is unique to test_a, line 4 is uncovered
+    assert!(html.contains(
+        "<tr class=\"covered\" data-tests=\"0 1\" data-location=\"Line 2\"><td class=\"count\">2</td><td class=\"number\">2</td><td class=\"code\">    a &lt; 3</td></tr>"
+    ));
+    assert!(html.contains("<tr class=\"unique\" style=\"--hue: 0\" data-tests=\"0\""));
+    assert!(
+        html.contains(
+            "<tr class=\"uncovered\"><td class=\"count\">0</td><td class=\"number\">4</td>"
+        )
+    );
+    assert!(html.contains("const TESTS = [\"tests::test_a\",\"tests::test_b\"];"));
+}
+
+#[test]
+fn test_render_html_without_source() {
+    // Setup test data
+    let mut coverage = collected_with_source(&[("tests::test_a", &[1])], SOURCE);
+    coverage.sources.clear();
+    let analysis = build_analysis("demolib", &coverage, false);
+
+    // Execute
+    let html = render_html(&coverage, &analysis);
+
+    // Verify
+    assert!(html.contains("<h2>src/lib.rs</h2>\n<p>Source not available.</p>"));
+}
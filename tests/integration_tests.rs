@@ -83,7 +83,7 @@ fn test_coverage_analysis_demo_lib() {
 
     // Find test_foo
     let test_foo = tests
-        .get("tests::test_foo")
+        .get("lib:demolib::tests::test_foo")
         .expect("test_foo not found in report");
     let test_foo_unique = test_foo["unique_covered_lines"]
         .as_u64()
@@ -92,7 +92,7 @@ fn test_coverage_analysis_demo_lib() {
 
     // Find test_not_bar
     let test_not_bar = tests
-        .get("tests::test_not_bar")
+        .get("lib:demolib::tests::test_not_bar")
         .expect("test_not_bar not found in report");
     let test_not_bar_unique = test_not_bar["unique_covered_lines"]
         .as_u64()
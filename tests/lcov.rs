@@ -0,0 +1,120 @@
+use isotarp::coverage::analysis::build_analysis;
+use isotarp::export::lcov::{lcov_test_name, render_lcov};
+use isotarp::types::models::CollectedCoverage;
+use std::collections::HashMap;
+
+// A test's (line, hits) pairs in a file
+type Entry<'a> = (&'a str, &'a str, &'a [(u64, u64)]);
+
+// Helper to collect coverage from (test, file, [(line, hits)]) entries
+fn collected(entries: &[Entry]) -> CollectedCoverage {
+    let mut collected = CollectedCoverage::default();
+    for (test_name, file, lines) in entries {
+        collected
+            .tests
+            .entry(test_name.to_string())
+            .or_default()
+            .insert(
+                file.to_string(),
+                lines.iter().map(|(line, _)| *line).collect(),
+            );
+        collected
+            .hits
+            .entry(test_name.to_string())
+            .or_insert_with(HashMap::new)
+            .insert(file.to_string(), lines.iter().copied().collect());
+    }
+    collected
+        .coverable
+        .insert("src/lib.rs".to_string(), (1..=4).collect());
+    collected
+        .coverable
+        .insert("src/unused.rs".to_string(), (1..=2).collect());
+    collected
+}
+
+#[test]
+fn test_lcov_test_name() {
+    assert_eq!(lcov_test_name("tests::test_foo"), "tests__test_foo");
+}
+
+#[test]
+fn test_render_lcov_sections_per_test() {
+    // Setup test data
+    let coverage = collected(&[
+        ("tests::test_a", "src/lib.rs", &[(1, 2), (2, 1)]),
+        ("tests::test_b", "src/lib.rs", &[(2, 5)]),
+    ]);
+    let analysis = build_analysis("demolib", &coverage, false);
+
+    // Execute
+    let lcov = render_lcov(&coverage, &analysis);
+
+    // Verify
+    let expected = "\
+TN:tests__test_a
+SF:src/lib.rs
+DA:1,2
+DA:2,1
+DA:3,0
+DA:4,0
+LF:4
+LH:2
+end_of_record
+TN:tests__test_b
+SF:src/lib.rs
+DA:1,0
+DA:2,5
+DA:3,0
+DA:4,0
+LF:4
+LH:1
+end_of_record
+TN:
+SF:src/unused.rs
+DA:1,0
+DA:2,0
+LF:2
+LH:0
+end_of_record
+";
+    assert_eq!(lcov, expected);
+}
+
+#[test]
+fn test_render_lcov_functions() {
+    // Setup test data
+    let mut coverage = collected(&[("tests::test_a", "src/lib.rs", &[(2, 3)])]);
+    coverage.sources.insert(
+        "src/lib.rs".to_string(),
+        "pub fn foo() {\n    1;\n}\npub fn bar() {}\n".to_string(),
+    );
+    let analysis = build_analysis("demolib", &coverage, false);
+
+    // Execute
+    let lcov = render_lcov(&coverage, &analysis);
+
+    // Verify
+    assert!(lcov.contains("FN:4,bar\nFN:1,foo\nFNDA:0,bar\nFNDA:3,foo\nFNF:2\nFNH:1\n"));
+}
+
+#[test]
+fn test_render_lcov_keeps_colliding_test_names_apart() {
+    // Setup test data - both names become "lib_a__b__c" in LCOV
+    let coverage = collected(&[
+        ("lib:a::b::c", "src/lib.rs", &[(1, 1)]),
+        ("lib:a::b__c", "src/lib.rs", &[(2, 1)]),
+    ]);
+    let analysis = build_analysis("demolib", &coverage, false);
+
+    // Execute
+    let lcov = render_lcov(&coverage, &analysis);
+
+    // Verify
+    let test_names: Vec<&str> = lcov
+        .lines()
+        .filter_map(|line| line.strip_prefix("TN:"))
+        .filter(|test_name| !test_name.is_empty())
+        .collect();
+    assert_eq!(test_names, vec!["lib_a__b__c", "lib_a__b__c_2"]);
+}
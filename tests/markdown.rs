@@ -0,0 +1,64 @@
+mod common;
+
+use common::collected;
+use isotarp::coverage::analysis::build_analysis;
+use isotarp::export::markdown::render_markdown;
+
+#[test]
+fn test_render_markdown_categories() {
+    // Setup test data
+    let coverage = collected(&[
+        ("tests::test_big", &[1, 2, 3]),
+        ("tests::test_small", &[2]),
+        ("tests::test_none", &[]),
+    ]);
+    let analysis = build_analysis("demolib", &coverage, false);
+
+    // Execute
+    let markdown = render_markdown(&analysis, false);
+
+    // Verify
+    assert!(
+        markdown.contains("3 tests cover **3 of 6** coverable lines (50.0%), leaving 3 uncovered.")
+    );
+    assert!(markdown.contains(
+        "<summary><strong>Tests with unique line coverage (1)</strong></summary>\n\n| Test | Unique lines | Covered lines | Unique share |\n| --- | ---: | ---: | ---: |\n| `tests::test_big` | 2 | 3 | 66.7% |\n"
+    ));
+    assert!(markdown.contains("| `tests::test_small` | 1 | `tests::test_big` |"));
+    assert!(markdown.contains("- `tests::test_none`"));
+    assert!(!markdown.contains("Files ("));
+}
+
+#[test]
+fn test_render_markdown_per_file() {
+    // Setup test data
+    let coverage = collected(&[("tests::test_a", &[1, 2, 4])]);
+    let analysis = build_analysis("demolib", &coverage, false);
+
+    // Execute
+    let markdown = render_markdown(&analysis, true);
+
+    // Verify
+    assert!(markdown.contains("| `src/lib.rs` | 3 | 1-2, 4 |"));
+    assert!(markdown.contains("| `src/lib.rs` | 3 | 6 | 3, 5-6 |"));
+}
+
+#[test]
+fn test_render_markdown_escapes_test_names() {
+    // Setup test data
+    let coverage = collected(&[
+        ("src/lib.rs - Wrapper<T>::get (line 3)", &[1]),
+        ("tests::`quoted`", &[]),
+        ("tests::a|b", &[]),
+    ]);
+    let analysis = build_analysis("demolib", &coverage, false);
+
+    // Execute
+    let markdown = render_markdown(&analysis, true);
+
+    // Verify
+    assert!(markdown.contains("<code>src/lib.rs - Wrapper&lt;T&gt;::get (line 3)</code>"));
+    assert!(markdown.contains("| `src/lib.rs - Wrapper<T>::get (line 3)` | 1 |"));
+    assert!(markdown.contains("- `` tests::`quoted` ``\n"));
+    assert!(markdown.contains("- `tests::a|b`\n"));
+}
@@ -0,0 +1,141 @@
+mod common;
+
+use common::collected;
+use isotarp::coverage::analysis::build_analysis;
+use isotarp::coverage::merge::merge_partials;
+use isotarp::resolve::shard_tests;
+use isotarp::types::models::{PartialCoverage, SCHEMA_VERSION, Shard};
+use isotarp::utils::io::{load_partial, save_partial};
+use isotarp::{Error, save_analysis};
+use temp_testdir::TempDir;
+
+// Helper to build a shard of a run of tests test_a to test_c, without doctests
+fn partial(shard: &str, tests: &[(&str, &[u64])]) -> PartialCoverage {
+    PartialCoverage {
+        schema_version: SCHEMA_VERSION,
+        package: "demolib".to_string(),
+        shard: shard.parse().unwrap(),
+        tests: ["tests::test_a", "tests::test_b", "tests::test_c"]
+            .iter()
+            .map(|test_name| test_name.to_string())
+            .collect(),
+        doctests: false,
+        coverage: collected(tests),
+    }
+}
+
+#[test]
+fn test_shard_tests_partition() {
+    // Setup test data
+    let tests: Vec<String> = ["c", "a", "e", "b", "d"]
+        .iter()
+        .map(|name| name.to_string())
+        .collect();
+    let mut reversed = tests.clone();
+    reversed.reverse();
+
+    // Execute
+    let shards: Vec<Vec<String>> = (1..=2)
+        .map(|index| shard_tests(&tests, Shard { index, count: 2 }))
+        .collect();
+
+    // Verify - shards are disjoint, complete, and independent of the input order
+    assert_eq!(shards[0], vec!["a", "c", "e"]);
+    assert_eq!(shards[1], vec!["b", "d"]);
+    assert_eq!(
+        shard_tests(&reversed, Shard { index: 2, count: 2 }),
+        shards[1]
+    );
+}
+
+#[test]
+fn test_shard_parse() {
+    assert_eq!("2/4".parse(), Ok(Shard { index: 2, count: 4 }));
+    assert!("0/4".parse::<Shard>().is_err());
+    assert!("5/4".parse::<Shard>().is_err());
+    assert!("2".parse::<Shard>().is_err());
+}
+
+#[test]
+fn test_merge_matches_unsharded_analysis() {
+    // Setup test data - uniqueness of line 2 is only visible across shards
+    let temp_dir = TempDir::default();
+    let part_path = temp_dir.join("part1.json");
+    save_partial(
+        &partial(
+            "1/2",
+            &[("tests::test_a", &[1, 2]), ("tests::test_c", &[4])],
+        ),
+        &part_path,
+    )
+    .unwrap();
+    let parts = vec![
+        load_partial(&part_path).unwrap(),
+        partial("2/2", &[("tests::test_b", &[2, 3])]),
+    ];
+
+    // Execute
+    let merged = build_analysis("demolib", &merge_partials(parts).unwrap(), false);
+    let whole = build_analysis(
+        "demolib",
+        &collected(&[
+            ("tests::test_a", &[1, 2]),
+            ("tests::test_b", &[2, 3]),
+            ("tests::test_c", &[4]),
+        ]),
+        false,
+    );
+
+    // Verify - both analyses save to identical reports
+    let merged_path = temp_dir.join("merged.json");
+    let whole_path = temp_dir.join("whole.json");
+    save_analysis(&merged, &merged_path).unwrap();
+    save_analysis(&whole, &whole_path).unwrap();
+    assert_eq!(
+        std::fs::read_to_string(merged_path).unwrap(),
+        std::fs::read_to_string(whole_path).unwrap()
+    );
+    assert_eq!(merged.tests["tests::test_a"].unique_covered_lines, 1);
+}
+
+#[test]
+fn test_merge_rejects_incomplete_shards() {
+    // Execute
+    let missing = merge_partials(vec![partial("1/3", &[]), partial("3/3", &[])]);
+    let duplicate = merge_partials(vec![partial("1/2", &[]), partial("1/2", &[])]);
+
+    // Verify
+    assert!(matches!(missing, Err(Error::MergeFailed(message)) if message.contains("2/3")));
+    assert!(matches!(duplicate, Err(Error::MergeFailed(_))));
+}
+
+#[test]
+fn test_merge_rejects_test_in_several_shards() {
+    // Execute
+    let result = merge_partials(vec![
+        partial("1/2", &[("tests::test_a", &[1])]),
+        partial("2/2", &[("tests::test_a", &[2])]),
+    ]);
+
+    // Verify
+    assert!(
+        matches!(result, Err(Error::MergeFailed(message)) if message.contains("tests::test_a"))
+    );
+}
+
+#[test]
+fn test_merge_rejects_shards_of_different_runs() {
+    // Setup test data
+    let mut other_selection = partial("2/2", &[]);
+    other_selection.tests.pop();
+    let mut with_doctests = partial("2/2", &[]);
+    with_doctests.doctests = true;
+
+    // Execute
+    let selection = merge_partials(vec![partial("1/2", &[]), other_selection]);
+    let doctests = merge_partials(vec![partial("1/2", &[]), with_doctests]);
+
+    // Verify
+    assert!(matches!(selection, Err(Error::MergeFailed(message)) if message.contains("selection")));
+    assert!(matches!(doctests, Err(Error::MergeFailed(message)) if message.contains("--doctests")));
+}
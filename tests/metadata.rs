@@ -0,0 +1,96 @@
+use isotarp::utils::metadata::{
+    parse_package_roots, parse_workspace_members, parse_workspace_root,
+};
+use std::path::Path;
+
+#[test]
+fn test_parse_workspace_members() {
+    // Setup test data - trimmed output of `cargo metadata --no-deps --format-version 1`
+    let metadata = r#"{
+        "packages": [
+            {"name": "core", "manifest_path": "/ws/core/Cargo.toml"},
+            {"name": "cli", "manifest_path": "/ws/cli/Cargo.toml"}
+        ],
+        "workspace_members": ["path+file:///ws/core#0.1.0", "path+file:///ws/cli#0.1.0"],
+        "workspace_root": "/ws"
+    }"#;
+
+    // Execute
+    let members = parse_workspace_members(metadata).unwrap();
+
+    // Verify
+    assert_eq!(members, vec!["core", "cli"]);
+    assert_eq!(parse_workspace_root(metadata).unwrap(), Path::new("/ws"));
+}
+
+#[test]
+fn test_parse_workspace_members_rejects_invalid_output() {
+    // Execute & Verify
+    assert!(parse_workspace_members("not json").is_err());
+    assert!(parse_workspace_members("{}").is_err());
+}
+
+#[test]
+fn test_parse_package_roots() {
+    // Setup test data
+    let metadata = r#"{
+        "packages": [
+            {"name": "core", "manifest_path": "/ws/crates/core-impl/Cargo.toml"},
+            {"name": "cli", "manifest_path": "/ws/cli/Cargo.toml"}
+        ],
+        "workspace_root": "/ws"
+    }"#;
+
+    // Execute
+    let single = parse_package_roots(metadata, &["core".to_string()], &[]).unwrap();
+    let both =
+        parse_package_roots(metadata, &["core".to_string(), "cli".to_string()], &[]).unwrap();
+
+    // Verify - paths are relative to the workspace root, whichever packages are analyzed
+    assert_eq!(single.base(), Path::new("/ws"));
+    assert_eq!(both.base(), Path::new("/ws"));
+    assert_eq!(
+        single.relative_path(Path::new("/ws/crates/core-impl/src/lib.rs")),
+        Some("crates/core-impl/src/lib.rs".to_string())
+    );
+    assert_eq!(
+        both.relative_path(Path::new("/ws/crates/core-impl/src/lib.rs")),
+        Some("crates/core-impl/src/lib.rs".to_string())
+    );
+    assert_eq!(single.relative_path(Path::new("/ws/cli/src/main.rs")), None);
+    assert_eq!(
+        both.absolute_path("cli/src/main.rs"),
+        Path::new("/ws/cli/src/main.rs")
+    );
+    assert!(parse_package_roots(metadata, &["missing".to_string()], &[]).is_err());
+}
+
+#[test]
+fn test_parse_package_roots_with_included_crates() {
+    // Setup test data - output with dependencies, listing a path and a registry dependency
+    let metadata = r#"{
+        "packages": [
+            {"name": "app", "manifest_path": "/ws/app/Cargo.toml", "source": null},
+            {"name": "util", "manifest_path": "/vendor/util/Cargo.toml", "source": null},
+            {
+                "name": "serde",
+                "manifest_path": "/cargo/registry/serde-1.0.0/Cargo.toml",
+                "source": "registry+https://github.com/rust-lang/crates.io-index"
+            }
+        ],
+        "workspace_root": "/ws"
+    }"#;
+    let app = vec!["app".to_string()];
+
+    // Execute
+    let roots = parse_package_roots(metadata, &app, &["util".to_string()]).unwrap();
+
+    // Verify - path dependencies can be included, registry dependencies cannot
+    assert_eq!(roots.reported_crates(), vec!["app", "util"]);
+    assert!(roots.spans_several_crates());
+    assert_eq!(
+        roots.relative_path(Path::new("/vendor/util/src/lib.rs")),
+        Some("/vendor/util/src/lib.rs".to_string())
+    );
+    assert!(parse_package_roots(metadata, &app, &["serde".to_string()]).is_err());
+}
@@ -0,0 +1,108 @@
+mod common;
+
+use common::coverage;
+use isotarp::coverage::minimize::minimize_test_suite;
+use std::collections::HashMap;
+
+#[test]
+fn test_minimize_drops_subset_test() {
+    // Setup test data: test_small only covers lines test_big also covers
+    let mut results = HashMap::new();
+    results.insert(
+        "tests::test_big".to_string(),
+        coverage(&[("src/lib.rs", &[1, 2, 3, 4])]),
+    );
+    results.insert(
+        "tests::test_small".to_string(),
+        coverage(&[("src/lib.rs", &[2, 3])]),
+    );
+
+    // Execute
+    let minimization = minimize_test_suite(&results);
+
+    // Verify
+    assert_eq!(minimization.total_covered_lines, 4);
+    assert_eq!(minimization.kept.len(), 1);
+    assert_eq!(minimization.kept[0].name, "tests::test_big");
+    assert_eq!(minimization.kept[0].new_covered_lines, 4);
+    assert_eq!(
+        minimization.droppable,
+        vec!["tests::test_small".to_string()]
+    );
+}
+
+#[test]
+fn test_minimize_keeps_jointly_needed_tests() {
+    // Setup test data: neither a nor b has unique lines once c is present,
+    // but c alone is not enough and a + b together cover everything
+    let mut results = HashMap::new();
+    results.insert(
+        "tests::test_a".to_string(),
+        coverage(&[("src/lib.rs", &[1, 2, 3]), ("src/functions.rs", &[10])]),
+    );
+    results.insert(
+        "tests::test_b".to_string(),
+        coverage(&[("src/lib.rs", &[4, 5, 6]), ("src/functions.rs", &[11])]),
+    );
+    results.insert(
+        "tests::test_c".to_string(),
+        coverage(&[("src/lib.rs", &[3, 4]), ("src/functions.rs", &[10, 11])]),
+    );
+
+    // Execute
+    let minimization = minimize_test_suite(&results);
+
+    // Verify
+    let kept: Vec<&str> = minimization.kept.iter().map(|k| k.name.as_str()).collect();
+    assert_eq!(kept, vec!["tests::test_a", "tests::test_b"]);
+    assert_eq!(minimization.droppable, vec!["tests::test_c".to_string()]);
+    assert_eq!(minimization.total_covered_lines, 8);
+}
+
+#[test]
+fn test_minimize_prunes_redundant_greedy_pick() {
+    // Setup test data: greedy picks test_wide first, which test_left and test_right
+    // then make redundant
+    let mut results = HashMap::new();
+    results.insert(
+        "tests::test_wide".to_string(),
+        coverage(&[("src/lib.rs", &[2, 3, 4, 5])]),
+    );
+    results.insert(
+        "tests::test_left".to_string(),
+        coverage(&[("src/lib.rs", &[1, 2, 3])]),
+    );
+    results.insert(
+        "tests::test_right".to_string(),
+        coverage(&[("src/lib.rs", &[4, 5, 6])]),
+    );
+
+    // Execute
+    let minimization = minimize_test_suite(&results);
+
+    // Verify
+    let kept: Vec<&str> = minimization.kept.iter().map(|k| k.name.as_str()).collect();
+    assert_eq!(kept, vec!["tests::test_left", "tests::test_right"]);
+    assert_eq!(minimization.droppable, vec!["tests::test_wide".to_string()]);
+}
+
+#[test]
+fn test_minimize_zero_coverage_tests_are_droppable() {
+    // Setup test data
+    let mut results = HashMap::new();
+    results.insert(
+        "tests::test_foo".to_string(),
+        coverage(&[("src/lib.rs", &[1])]),
+    );
+    results.insert("tests::test_not_bar".to_string(), HashMap::new());
+
+    // Execute
+    let minimization = minimize_test_suite(&results);
+
+    // Verify
+    assert_eq!(minimization.kept.len(), 1);
+    assert_eq!(
+        minimization.droppable,
+        vec!["tests::test_not_bar".to_string()]
+    );
+}
@@ -0,0 +1,40 @@
+use isotarp::utils::paths::{test_name_from_path, test_name_to_path_segment};
+use std::path::Path;
+
+#[test]
+fn test_path_segment_escapes_separators() {
+    assert_eq!(
+        test_name_to_path_segment("lib:demolib::tests::test_name"),
+        "lib%3Ademolib/tests/test_name"
+    );
+    assert_eq!(
+        test_name_to_path_segment("doc:demolib::src/lib.rs - foo (line 3)"),
+        "doc%3Ademolib/src%2Flib.rs - foo (line 3)"
+    );
+}
+
+#[test]
+fn test_path_segment_round_trip() {
+    for test_name in [
+        "lib:demolib::tests::test_name",
+        "core/test:integration::tests::it_works",
+        "doc:demolib::src/lib.rs - Foo::bar (line 7)",
+        "tests::literal_%3A_and_%2F_and_%",
+    ] {
+        let path = test_name_to_path_segment(test_name);
+        assert!(!path.contains(':'));
+        assert_eq!(
+            test_name_from_path(Path::new(&path)).as_deref(),
+            Some(test_name)
+        );
+    }
+}
+
+#[test]
+fn test_path_with_unescaped_colon_is_read() {
+    // Reports laid out by earlier versions kept the ':' of qualified names
+    assert_eq!(
+        test_name_from_path(Path::new("lib:demolib/tests/test_name")).as_deref(),
+        Some("lib:demolib::tests::test_name")
+    );
+}
@@ -0,0 +1,113 @@
+use isotarp::coverage::progress::{ProgressHeader, ProgressManifest, open_progress};
+use isotarp::types::models::TestCoverage;
+use isotarp::utils::paths::progress_manifest_path;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use temp_testdir::TempDir;
+
+// Helper to describe a run of the given package's tests
+fn header(package_name: &str) -> ProgressHeader {
+    let tests = ["tests::test_foo", "tests::test_bar", "tests::test_baz"];
+    let tests: Vec<String> = tests.iter().map(|name| name.to_string()).collect();
+    ProgressHeader::new(package_name, false, &tests)
+}
+
+// Helper to build a test's coverage of the given lines of src/lib.rs
+fn coverage(lines: &[u64]) -> TestCoverage {
+    TestCoverage {
+        covered: HashMap::from([(
+            "src/lib.rs".to_string(),
+            lines.iter().copied().collect::<HashSet<u64>>(),
+        )]),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_resume_returns_completed_tests() {
+    // Setup test data
+    let temp_dir = TempDir::default();
+    let manifest = ProgressManifest::create(&temp_dir, &header("demolib")).unwrap();
+    manifest
+        .record("tests::test_foo", &coverage(&[1, 2]))
+        .unwrap();
+    manifest.record("tests::test_bar", &coverage(&[3])).unwrap();
+    drop(manifest);
+
+    // Execute
+    let (manifest, completed) = open_progress(&temp_dir, &header("demolib"), true).unwrap();
+    manifest.record("tests::test_baz", &coverage(&[4])).unwrap();
+    drop(manifest);
+
+    // Verify
+    assert_eq!(completed.len(), 2);
+    assert_eq!(
+        completed["tests::test_foo"].covered,
+        coverage(&[1, 2]).covered
+    );
+    let (_, completed) = open_progress(&temp_dir, &header("demolib"), true).unwrap();
+    assert_eq!(completed.len(), 3);
+}
+
+#[test]
+fn test_resume_skips_line_cut_short() {
+    // Setup test data - the last entry was being written when the run died
+    let temp_dir = TempDir::default();
+    let manifest = ProgressManifest::create(&temp_dir, &header("demolib")).unwrap();
+    manifest.record("tests::test_foo", &coverage(&[1])).unwrap();
+    drop(manifest);
+    let path = progress_manifest_path(&temp_dir);
+    let mut content = fs::read_to_string(&path).unwrap();
+    content.push_str("{\"test\":\"tests::test_bar\",\"cov");
+    fs::write(&path, content).unwrap();
+
+    // Execute
+    let (manifest, completed) = open_progress(&temp_dir, &header("demolib"), true).unwrap();
+    manifest.record("tests::test_bar", &coverage(&[2])).unwrap();
+    drop(manifest);
+
+    // Verify
+    assert_eq!(completed.len(), 1);
+    assert!(completed.contains_key("tests::test_foo"));
+    let (_, completed) = open_progress(&temp_dir, &header("demolib"), true).unwrap();
+    assert_eq!(completed.len(), 2);
+}
+
+#[test]
+fn test_resume_refuses_other_parameters() {
+    // Setup test data
+    let temp_dir = TempDir::default();
+    let manifest = ProgressManifest::create(&temp_dir, &header("demolib")).unwrap();
+    manifest.record("tests::test_foo", &coverage(&[1])).unwrap();
+    drop(manifest);
+    let fewer_tests = ProgressHeader::new("demolib", false, &["tests::test_foo".to_string()]);
+    let with_doctests = ProgressHeader {
+        doctests: true,
+        ..header("demolib")
+    };
+
+    // Execute & Verify - a manifest for another package, test selection or doctest setting
+    // is not resumed, and is left for a run with matching parameters
+    assert!(open_progress(&temp_dir, &header("otherlib"), true).is_err());
+    assert!(open_progress(&temp_dir, &fewer_tests, true).is_err());
+    assert!(open_progress(&temp_dir, &with_doctests, true).is_err());
+    let (_, completed) = open_progress(&temp_dir, &header("demolib"), true).unwrap();
+    assert_eq!(completed.len(), 1);
+}
+
+#[test]
+fn test_fresh_run_starts_new_manifest() {
+    // Setup test data
+    let temp_dir = TempDir::default();
+    let manifest = ProgressManifest::create(&temp_dir, &header("demolib")).unwrap();
+    manifest.record("tests::test_foo", &coverage(&[1])).unwrap();
+    drop(manifest);
+
+    // Execute
+    let (_, completed) = open_progress(&temp_dir, &header("demolib"), false).unwrap();
+
+    // Verify
+    assert!(completed.is_empty());
+    let (_, completed) = open_progress(&temp_dir, &header("demolib"), true).unwrap();
+    assert!(completed.is_empty());
+}
@@ -0,0 +1,15 @@
+use isotarp::utils::ranges::{format_line_ranges, line_ranges};
+
+#[test]
+fn test_line_ranges_collapse_consecutive_lines() {
+    assert_eq!(
+        line_ranges(&[9, 1, 2, 3, 7, 10, 2]),
+        vec![(1, 3), (7, 7), (9, 10)]
+    );
+}
+
+#[test]
+fn test_format_line_ranges() {
+    assert_eq!(format_line_ranges(&[1, 2, 3, 7, 9, 10]), "1-3, 7, 9-10");
+    assert_eq!(format_line_ranges(&[]), "");
+}
@@ -0,0 +1,125 @@
+use isotarp::coverage::analysis::build_analysis;
+use isotarp::types::models::{CollectedCoverage, SCHEMA_VERSION};
+use isotarp::{Error, IsotarpAnalysis, Report, save_analysis};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use temp_testdir::TempDir;
+
+// Helper to build an analysis from (test, file, lines) entries
+fn analysis(entries: &[(&str, &str, &[u64])], line_index: bool) -> IsotarpAnalysis {
+    let mut collected = CollectedCoverage::default();
+    for (test_name, file, lines) in entries {
+        collected
+            .tests
+            .entry(test_name.to_string())
+            .or_insert_with(HashMap::new)
+            .insert(
+                file.to_string(),
+                lines.iter().copied().collect::<HashSet<u64>>(),
+            );
+    }
+    build_analysis("demolib", &collected, line_index)
+}
+
+#[test]
+fn test_report_round_trip_and_queries() {
+    // Setup test data
+    let temp_dir = TempDir::default();
+    let path = temp_dir.join("analysis.json");
+    let original = analysis(
+        &[
+            ("tests::test_a", "/home/user/demolib/src/lib.rs", &[1, 2, 3]),
+            ("tests::test_a", "/home/user/demolib/src/util.rs", &[5]),
+            ("tests::test_b", "/home/user/demolib/src/lib.rs", &[3, 4]),
+        ],
+        true,
+    );
+    save_analysis(&original, &path).unwrap();
+
+    // Execute
+    let report = Report::load(&path).unwrap();
+
+    // Verify
+    assert_eq!(report.analysis().schema_version, SCHEMA_VERSION);
+    assert!(report.has_line_index());
+    assert_eq!(report.tests(), vec!["tests::test_a", "tests::test_b"]);
+    assert_eq!(
+        report.find_files("src/lib.rs"),
+        vec!["/home/user/demolib/src/lib.rs"]
+    );
+    assert_eq!(
+        report.tests_for_line("/home/user/demolib/src/lib.rs", 3),
+        Some(vec!["tests::test_a", "tests::test_b"])
+    );
+    assert_eq!(
+        report.tests_for_line("/home/user/demolib/src/lib.rs", 9),
+        Some(vec![])
+    );
+    assert_eq!(
+        report.unique_lines("tests::test_a"),
+        Some(BTreeMap::from([
+            ("/home/user/demolib/src/lib.rs", vec![1, 2]),
+            ("/home/user/demolib/src/util.rs", vec![5]),
+        ]))
+    );
+    assert_eq!(
+        report.files_touched("tests::test_b"),
+        Some(vec!["/home/user/demolib/src/lib.rs"])
+    );
+    assert_eq!(report.files_touched("tests::test_missing"), None);
+}
+
+#[test]
+fn test_report_without_line_index() {
+    // Setup test data
+    let report = Report::from(analysis(&[("tests::test_a", "src/lib.rs", &[1, 2])], false));
+
+    // Execute & Verify - covering tests of a line are unknown without an index
+    assert!(!report.has_line_index());
+    assert!(!analysis(&[], true).has_line_index());
+    assert_eq!(report.tests_for_line("src/lib.rs", 1), None);
+    assert_eq!(
+        report.unique_lines("tests::test_a"),
+        Some(BTreeMap::from([("src/lib.rs", vec![1, 2])]))
+    );
+}
+
+#[test]
+fn test_report_rejects_newer_schema() {
+    // Setup test data
+    let temp_dir = TempDir::default();
+    let path = temp_dir.join("analysis.json");
+    let mut newer = analysis(&[("tests::test_a", "src/lib.rs", &[1])], false);
+    newer.schema_version = SCHEMA_VERSION + 1;
+    fs::write(&path, serde_json::to_string(&newer).unwrap()).unwrap();
+
+    // Execute
+    let result = Report::load(&path);
+
+    // Verify
+    assert!(matches!(
+        result,
+        Err(Error::UnsupportedSchema(version, supported))
+            if version == SCHEMA_VERSION + 1 && supported == SCHEMA_VERSION
+    ));
+}
+
+#[test]
+fn test_report_rejects_unversioned_schema() {
+    // Setup test data
+    let temp_dir = TempDir::default();
+    let path = temp_dir.join("analysis.json");
+    let mut value =
+        serde_json::to_value(analysis(&[("tests::test_a", "src/lib.rs", &[1])], false)).unwrap();
+    value.as_object_mut().unwrap().remove("schema_version");
+    fs::write(&path, value.to_string()).unwrap();
+
+    // Execute
+    let result = Report::load(&path);
+
+    // Verify
+    assert!(matches!(
+        result,
+        Err(Error::OutdatedSchema(0, supported)) if supported == SCHEMA_VERSION
+    ));
+}
@@ -0,0 +1,115 @@
+mod common;
+
+use common::coverage;
+use isotarp::coverage::similarity::compute_test_similarity;
+use isotarp::types::models::SimilarityMetric;
+use std::collections::HashMap;
+
+// Helper to look up a similarity value by test names
+fn value(matrix: &[Vec<f64>], tests: &[String], a: &str, b: &str) -> f64 {
+    let i = tests.iter().position(|t| t == a).unwrap();
+    let j = tests.iter().position(|t| t == b).unwrap();
+    matrix[i][j]
+}
+
+#[test]
+fn test_similarity_matrix_values() {
+    // Setup test data
+    let mut results = HashMap::new();
+    results.insert(
+        "tests::test_a".to_string(),
+        coverage(&[("src/lib.rs", &[1, 2, 3, 4])]),
+    );
+    results.insert(
+        "tests::test_b".to_string(),
+        coverage(&[("src/lib.rs", &[3, 4])]),
+    );
+    results.insert("tests::test_not_bar".to_string(), HashMap::new());
+
+    // Execute
+    let similarity = compute_test_similarity(&results, SimilarityMetric::Jaccard, 0.9);
+
+    // Verify
+    let tests = &similarity.tests;
+    assert_eq!(
+        value(&similarity.jaccard, tests, "tests::test_a", "tests::test_b"),
+        0.5
+    );
+    assert_eq!(
+        value(&similarity.overlap, tests, "tests::test_a", "tests::test_b"),
+        1.0
+    );
+    assert_eq!(
+        value(&similarity.jaccard, tests, "tests::test_b", "tests::test_a"),
+        0.5
+    );
+    assert_eq!(
+        value(
+            &similarity.jaccard,
+            tests,
+            "tests::test_a",
+            "tests::test_not_bar"
+        ),
+        0.0
+    );
+    assert_eq!(
+        value(&similarity.jaccard, tests, "tests::test_a", "tests::test_a"),
+        1.0
+    );
+    assert!(similarity.clusters.is_empty());
+}
+
+#[test]
+fn test_similarity_clusters_by_metric() {
+    // Setup test data
+    let mut results = HashMap::new();
+    results.insert(
+        "tests::test_a".to_string(),
+        coverage(&[("src/lib.rs", &[1, 2, 3, 4])]),
+    );
+    results.insert(
+        "tests::test_b".to_string(),
+        coverage(&[("src/lib.rs", &[3, 4])]),
+    );
+    results.insert(
+        "tests::test_c".to_string(),
+        coverage(&[("src/functions.rs", &[9])]),
+    );
+
+    // Execute
+    let by_jaccard = compute_test_similarity(&results, SimilarityMetric::Jaccard, 0.5);
+    let by_overlap = compute_test_similarity(&results, SimilarityMetric::Overlap, 0.9);
+
+    // Verify
+    let expected = vec![vec![
+        "tests::test_a".to_string(),
+        "tests::test_b".to_string(),
+    ]];
+    assert_eq!(by_jaccard.clusters, expected);
+    assert_eq!(by_overlap.clusters, expected);
+}
+
+#[test]
+fn test_similarity_clusters_are_transitive() {
+    // Setup test data: a ~ b and b ~ c, but a and c share nothing
+    let mut results = HashMap::new();
+    results.insert(
+        "tests::test_a".to_string(),
+        coverage(&[("src/lib.rs", &[1, 2])]),
+    );
+    results.insert(
+        "tests::test_b".to_string(),
+        coverage(&[("src/lib.rs", &[2, 3])]),
+    );
+    results.insert(
+        "tests::test_c".to_string(),
+        coverage(&[("src/lib.rs", &[3, 4])]),
+    );
+
+    // Execute
+    let similarity = compute_test_similarity(&results, SimilarityMetric::Jaccard, 0.3);
+
+    // Verify
+    assert_eq!(similarity.clusters.len(), 1);
+    assert_eq!(similarity.clusters[0].len(), 3);
+}
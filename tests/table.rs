@@ -0,0 +1,95 @@
+use isotarp::coverage::analysis::build_analysis;
+use isotarp::export::table::{render_coverage_table, render_line_table};
+use isotarp::types::models::CollectedCoverage;
+use isotarp::utils::paths::line_table_path;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+// Helper to collect coverage from (test, file, lines) entries, hitting each line once
+fn collected(entries: &[(&str, &str, &[u64])]) -> CollectedCoverage {
+    let mut collected = CollectedCoverage::default();
+    for (test_name, file, lines) in entries {
+        collected
+            .hits
+            .entry(test_name.to_string())
+            .or_insert_with(HashMap::new)
+            .insert(
+                file.to_string(),
+                lines.iter().map(|line| (*line, 1)).collect(),
+            );
+        collected
+            .tests
+            .entry(test_name.to_string())
+            .or_insert_with(HashMap::new)
+            .insert(
+                file.to_string(),
+                lines.iter().copied().collect::<HashSet<u64>>(),
+            );
+    }
+    collected
+}
+
+#[test]
+fn test_render_coverage_table() {
+    // Setup test data
+    let coverage = collected(&[
+        ("tests::test_a", "src/lib.rs", &[1, 2, 3, 5]),
+        ("tests::test_a", "src/my,file.rs", &[7]),
+        ("tests::test_b", "src/lib.rs", &[3]),
+    ]);
+    let analysis = build_analysis("demolib", &coverage, false);
+
+    // Execute
+    let csv = render_coverage_table(&analysis, ',');
+    let tsv = render_coverage_table(&analysis, '\t');
+
+    // Verify
+    assert_eq!(
+        csv,
+        "test,file,covered_lines,unique_lines,unique_line_ranges\n\
+         tests::test_a,src/lib.rs,4,3,\"1-2, 5\"\n\
+         tests::test_a,\"src/my,file.rs\",1,1,7\n\
+         tests::test_b,src/lib.rs,1,0,\n"
+    );
+    assert_eq!(
+        tsv.lines().nth(1),
+        Some("tests::test_a\tsrc/lib.rs\t4\t3\t1-2, 5")
+    );
+}
+
+#[test]
+fn test_render_line_table() {
+    // Setup test data
+    let coverage = collected(&[
+        ("tests::test_a", "src/lib.rs", &[1, 2]),
+        ("tests::test_b", "src/lib.rs", &[2]),
+    ]);
+    let with_index = build_analysis("demolib", &coverage, true);
+    let without_index = build_analysis("demolib", &coverage, false);
+
+    // Execute
+    let full = render_line_table(&coverage, &with_index, ',');
+    let unindexed = render_line_table(&coverage, &without_index, ',');
+
+    // Verify - every covered line is listed, with or without a line index
+    assert_eq!(
+        full,
+        "test,file,line,unique,hits\n\
+         tests::test_a,src/lib.rs,1,true,1\n\
+         tests::test_a,src/lib.rs,2,false,1\n\
+         tests::test_b,src/lib.rs,2,false,1\n"
+    );
+    assert_eq!(unindexed, full);
+}
+
+#[test]
+fn test_line_table_path() {
+    assert_eq!(
+        line_table_path(Path::new("out/coverage.csv")),
+        PathBuf::from("out/coverage-lines.csv")
+    );
+    assert_eq!(
+        line_table_path(Path::new("coverage")),
+        PathBuf::from("coverage-lines")
+    );
+}
@@ -0,0 +1,436 @@
+use isotarp::coverage::analysis::collect_from_reports;
+use isotarp::coverage::tarpaulin::{
+    extract_coverable_lines, extract_covered_lines, extract_line_hits, parse_test_binaries,
+    reroot_report,
+};
+use isotarp::types::models::{
+    LineStat, SourceFile, TargetKind, TarpaulinReport, TestId, TestTarget, Trace,
+};
+use isotarp::utils::metadata::PackageRoots;
+use isotarp::utils::paths::test_report_path;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use temp_testdir::TempDir;
+
+// Helper to build a source file from (line, hits) traces
+fn source_file(path: &[&str], traces: &[(u64, u64)]) -> SourceFile {
+    SourceFile {
+        path: path.iter().map(|part| part.to_string()).collect(),
+        content: String::new(),
+        traces: traces
+            .iter()
+            .map(|(line, hits)| Trace {
+                line: *line,
+                stats: LineStat::Line(*hits),
+                address: HashSet::new(),
+                length: 1,
+            })
+            .collect(),
+        covered: traces.iter().filter(|(_, hits)| *hits > 0).count(),
+        coverable: traces.len(),
+    }
+}
+
+// Helper to find the roots of the given packages and included crates in a workspace at /ws
+fn workspace_roots_including(
+    members: &[(&str, &str)],
+    packages: &[&str],
+    include: &[&str],
+) -> PackageRoots {
+    let members = members
+        .iter()
+        .map(|(name, dir)| (name.to_string(), PathBuf::from(dir)))
+        .collect();
+    let names = |names: &[&str]| -> Vec<String> { names.iter().map(|n| n.to_string()).collect() };
+    PackageRoots::new(members, Path::new("/ws"), &names(packages), &names(include)).unwrap()
+}
+
+// Helper to find the roots of the given packages in a workspace at /ws
+fn workspace_roots(members: &[(&str, &str)], packages: &[&str]) -> PackageRoots {
+    workspace_roots_including(members, packages, &[])
+}
+
+// Helper to analyze just the demolib package
+fn roots() -> PackageRoots {
+    workspace_roots(&[("demolib", "/ws/demolib")], &["demolib"])
+}
+
+// Helper to wrap source files in a report
+fn report(files: Vec<SourceFile>) -> TarpaulinReport {
+    TarpaulinReport {
+        files,
+        coverage: 0.0,
+        covered: 0,
+        coverable: 0,
+    }
+}
+
+#[test]
+fn test_extract_covered_lines_skips_zero_hits() {
+    // Setup test data
+    let report = report(vec![source_file(
+        &["/", "ws", "demolib", "src", "lib.rs"],
+        &[(1, 2), (2, 0), (3, 1)],
+    )]);
+
+    // Execute
+    let covered = extract_covered_lines(&report, &roots());
+
+    // Verify
+    assert_eq!(covered["demolib/src/lib.rs"], HashSet::from([1, 3]));
+}
+
+#[test]
+fn test_extract_coverable_lines_keeps_zero_hits() {
+    // Setup test data
+    let report = report(vec![
+        source_file(&["/", "ws", "demolib", "src", "lib.rs"], &[(1, 2), (2, 0)]),
+        source_file(&["/", "ws", "demolib", "src", "functions.rs"], &[(5, 0)]),
+    ]);
+
+    // Execute
+    let covered = extract_covered_lines(&report, &roots());
+    let coverable = extract_coverable_lines(&report, &roots());
+
+    // Verify
+    assert_eq!(coverable["demolib/src/lib.rs"], HashSet::from([1, 2]));
+    assert_eq!(coverable["demolib/src/functions.rs"], HashSet::from([5]));
+    assert!(!covered.contains_key("demolib/src/functions.rs"));
+}
+
+#[test]
+fn test_extract_line_hits_keeps_counts() {
+    // Setup test data: line 3 has two traces for the same executions
+    let report = report(vec![source_file(
+        &["/", "ws", "demolib", "src", "lib.rs"],
+        &[(1, 4), (2, 0), (3, 7), (3, 7)],
+    )]);
+
+    // Execute
+    let hits = extract_line_hits(&report, &roots());
+
+    // Verify
+    let lib = &hits["demolib/src/lib.rs"];
+    assert_eq!(lib.len(), 2);
+    assert_eq!(lib[&1], 4);
+    assert_eq!(lib[&3], 7);
+}
+
+#[test]
+fn test_collect_from_reports_layout() {
+    // Setup test data - reports laid out as isotarp writes them, plus a stray one at the root
+    let temp_dir = TempDir::default();
+    let reports = [
+        ("lib:demolib::tests::test_foo", vec![(1, 1), (2, 0)]),
+        ("tests::nested::test_bar", vec![(1, 0), (2, 3)]),
+        ("doc:demolib::src/lib.rs - Foo::bar (line 7)", vec![(2, 1)]),
+    ];
+    for (test_name, traces) in &reports {
+        let path = test_report_path(&temp_dir, test_name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let report = report(vec![source_file(
+            &["/", "ws", "demolib", "src", "lib.rs"],
+            traces,
+        )]);
+        fs::write(path, serde_json::to_string(&report).unwrap()).unwrap();
+    }
+    fs::write(temp_dir.join("tarpaulin-report.json"), "{}").unwrap();
+
+    // Execute
+    let collected = collect_from_reports(&roots(), &temp_dir).unwrap();
+
+    // Verify
+    assert_eq!(collected.tests.len(), 3);
+    assert_eq!(
+        collected.tests["lib:demolib::tests::test_foo"]["demolib/src/lib.rs"],
+        HashSet::from([1])
+    );
+    assert_eq!(
+        collected.tests["tests::nested::test_bar"]["demolib/src/lib.rs"],
+        HashSet::from([2])
+    );
+    assert_eq!(
+        collected.tests["doc:demolib::src/lib.rs - Foo::bar (line 7)"]["demolib/src/lib.rs"],
+        HashSet::from([2])
+    );
+    assert_eq!(
+        collected.coverable["demolib/src/lib.rs"],
+        HashSet::from([1, 2])
+    );
+}
+
+#[test]
+fn test_parse_test_binaries() {
+    // Setup test data - cargo messages for a lib, an integration test and a build script
+    let output = [
+        r#"{"reason":"compiler-artifact","target":{"kind":["lib"],"name":"demolib"},"profile":{"test":true},"executable":"/t/demolib-1"}"#,
+        r#"{"reason":"compiler-artifact","target":{"kind":["test"],"name":"integration"},"profile":{"test":true},"executable":"/t/integration-2"}"#,
+        r#"{"reason":"compiler-artifact","target":{"kind":["lib"],"name":"demolib"},"profile":{"test":false},"executable":null}"#,
+        r#"{"reason":"build-finished","success":true}"#,
+    ]
+    .join("\n");
+
+    // Execute
+    let binaries = parse_test_binaries(&output);
+
+    // Verify
+    assert_eq!(binaries.len(), 2);
+    assert_eq!(binaries[0].target.kind, TargetKind::Lib);
+    assert_eq!(binaries[0].target.name, "demolib");
+    assert_eq!(binaries[1].target.kind, TargetKind::Test);
+    assert_eq!(
+        binaries[1].target.cargo_args(),
+        vec!["--test", "integration"]
+    );
+    assert_eq!(binaries[1].executable, PathBuf::from("/t/integration-2"));
+}
+
+#[test]
+fn test_test_id_round_trip() {
+    // Setup test data
+    let test_id = TestId {
+        package: None,
+        target: TestTarget {
+            kind: TargetKind::Test,
+            name: "integration".to_string(),
+        },
+        path: "tests::it_works".to_string(),
+    };
+
+    // Execute
+    let written = test_id.to_string();
+
+    // Verify
+    assert_eq!(written, "test:integration::tests::it_works");
+    assert_eq!(written.parse::<TestId>(), Ok(test_id));
+    assert_eq!(TestId::path_of(&written), "tests::it_works");
+
+    // Unqualified names, even ones starting with a target kind, are left alone
+    assert!("tests::it_works".parse::<TestId>().is_err());
+    assert!("lib::tests::it_works".parse::<TestId>().is_err());
+    assert_eq!(
+        TestId::path_of("lib::tests::it_works"),
+        "lib::tests::it_works"
+    );
+}
+
+#[test]
+fn test_doctest_id() {
+    // Setup test data
+    let name = "doc:demolib::src/lib.rs - Parser::parse (line 12)";
+
+    // Execute
+    let test_id: TestId = name.parse().unwrap();
+
+    // Verify - the doctest's path may itself contain "::"
+    assert_eq!(test_id.target.kind, TargetKind::Doc);
+    assert_eq!(test_id.target.name, "demolib");
+    assert_eq!(test_id.path, "src/lib.rs - Parser::parse (line 12)");
+    assert_eq!(test_id.target.cargo_args(), vec!["--doc"]);
+    assert_eq!(test_id.to_string(), name);
+}
+
+#[test]
+fn test_test_id_with_package() {
+    // Setup test data
+    let name = "core/test:integration::tests::it_works";
+
+    // Execute
+    let test_id: TestId = name.parse().unwrap();
+
+    // Verify
+    assert_eq!(test_id.package.as_deref(), Some("core"));
+    assert_eq!(test_id.target.kind, TargetKind::Test);
+    assert_eq!(test_id.target.name, "integration");
+    assert_eq!(test_id.to_string(), name);
+    assert_eq!(TestId::path_of(name), "tests::it_works");
+    assert!(
+        "/test:integration::tests::it_works"
+            .parse::<TestId>()
+            .is_err()
+    );
+}
+
+#[test]
+fn test_extract_covered_lines_across_packages() {
+    // Setup test data - a test in one package covering code in another
+    let report = report(vec![
+        source_file(&["/", "ws", "core", "src", "lib.rs"], &[(1, 1)]),
+        source_file(&["/", "ws", "cli", "src", "main.rs"], &[(4, 2)]),
+        source_file(&["/", "ws", "other", "src", "lib.rs"], &[(7, 1)]),
+    ]);
+    let roots = workspace_roots(
+        &[
+            ("core", "/ws/core"),
+            ("cli", "/ws/cli"),
+            ("other", "/ws/other"),
+        ],
+        &["core", "cli"],
+    );
+
+    // Execute
+    let covered = extract_covered_lines(&report, &roots);
+
+    // Verify - files of packages outside the analysis are left out, and the rest are named
+    // relative to the workspace root
+    assert_eq!(covered.len(), 2);
+    assert_eq!(covered["core/src/lib.rs"], HashSet::from([1]));
+    assert_eq!(covered["cli/src/main.rs"], HashSet::from([4]));
+}
+
+#[test]
+fn test_extract_covered_lines_by_manifest_dir() {
+    // Setup test data - the package lives in a directory named differently, next to one whose
+    // name starts with the package's and inside the workspace's root package
+    let report = report(vec![
+        source_file(
+            &["/", "ws", "crates", "foo-impl", "src", "lib.rs"],
+            &[(1, 1)],
+        ),
+        source_file(&["/", "ws", "crates", "foobar", "src", "lib.rs"], &[(2, 1)]),
+        source_file(&["/", "ws", "src", "main.rs"], &[(3, 1)]),
+        source_file(&["/", "home", "cargo", "foo", "src", "lib.rs"], &[(4, 1)]),
+    ]);
+    let members = [
+        ("app", "/ws"),
+        ("foo", "/ws/crates/foo-impl"),
+        ("foobar", "/ws/crates/foobar"),
+    ];
+
+    // Execute
+    let foo = extract_covered_lines(&report, &workspace_roots(&members, &["foo"]));
+    let app = extract_covered_lines(&report, &workspace_roots(&members, &["app"]));
+
+    // Verify - each file belongs to the package whose directory most closely encloses it
+    assert_eq!(foo.len(), 1);
+    assert_eq!(foo["crates/foo-impl/src/lib.rs"], HashSet::from([1]));
+    assert_eq!(app.len(), 1);
+    assert_eq!(app["src/main.rs"], HashSet::from([3]));
+}
+
+#[test]
+fn test_extract_covered_lines_of_included_crates() {
+    // Setup test data - a test of app reaching a workspace member and a path dependency
+    let report = report(vec![
+        source_file(&["/", "ws", "app", "src", "main.rs"], &[(1, 1)]),
+        source_file(&["/", "ws", "core", "src", "lib.rs"], &[(2, 1)]),
+        source_file(&["/", "vendor", "util", "src", "lib.rs"], &[(3, 1)]),
+        source_file(&["/", "ws", "other", "src", "lib.rs"], &[(4, 1)]),
+    ]);
+    let roots = workspace_roots_including(
+        &[
+            ("app", "/ws/app"),
+            ("core", "/ws/core"),
+            ("util", "/vendor/util"),
+            ("other", "/ws/other"),
+        ],
+        &["app"],
+        &["core", "util"],
+    );
+
+    // Execute
+    let covered = extract_covered_lines(&report, &roots);
+
+    // Verify - included crates are reported, relative to the workspace root if inside it
+    assert_eq!(covered.len(), 3);
+    assert_eq!(covered["app/src/main.rs"], HashSet::from([1]));
+    assert_eq!(covered["core/src/lib.rs"], HashSet::from([2]));
+    assert_eq!(covered["/vendor/util/src/lib.rs"], HashSet::from([3]));
+    assert_eq!(roots.crate_name("core/src/lib.rs"), Some("core"));
+    assert_eq!(roots.crate_name("/vendor/util/src/lib.rs"), Some("util"));
+}
+
+#[test]
+fn test_collect_from_reports_of_another_checkout() {
+    // Setup test data - a local workspace, and a report written by CI in another directory
+    let workspace = TempDir::default();
+    fs::create_dir_all(workspace.join("app").join("src")).unwrap();
+    fs::write(workspace.join("app").join("src").join("lib.rs"), "").unwrap();
+    let roots = PackageRoots::new(
+        vec![("app".to_string(), workspace.join("app"))],
+        &workspace,
+        &["app".to_string()],
+        &[],
+    )
+    .unwrap();
+    let reports_dir = workspace.join("reports");
+    let path = test_report_path(&reports_dir, "lib:app::tests::test_run");
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    let report = report(vec![
+        source_file(&["/", "ci", "build", "app", "src", "lib.rs"], &[(1, 1)]),
+        source_file(&["/", "ci", "cargo", "dep", "src", "lib.rs"], &[(2, 1)]),
+    ]);
+    fs::write(path, serde_json::to_string(&report).unwrap()).unwrap();
+
+    // Execute
+    let collected = collect_from_reports(&roots, &reports_dir).unwrap();
+
+    // Verify - files are moved to the local checkout and named relative to it
+    assert_eq!(
+        collected.tests["lib:app::tests::test_run"]["app/src/lib.rs"],
+        HashSet::from([1])
+    );
+    assert_eq!(collected.coverable.len(), 1);
+}
+
+#[test]
+fn test_reroot_report_keeps_members_apart_from_the_root_package() {
+    // Setup test data - the root package of a workspace with an app member, and a CI report
+    // listing app's file before the root package's
+    let workspace = TempDir::default();
+    for dir in [workspace.join("src"), workspace.join("app").join("src")] {
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("lib.rs"), "").unwrap();
+    }
+    let roots = PackageRoots::new(
+        vec![
+            ("root".to_string(), workspace.to_path_buf()),
+            ("app".to_string(), workspace.join("app")),
+        ],
+        &workspace,
+        &["root".to_string()],
+        &[],
+    )
+    .unwrap();
+    let mut report = report(vec![
+        source_file(&["/", "ci", "ws", "app", "src", "lib.rs"], &[(1, 1)]),
+        source_file(&["/", "ci", "ws", "src", "lib.rs"], &[(2, 1)]),
+    ]);
+
+    // Execute
+    reroot_report(&mut report, &roots);
+
+    // Verify - only the root package's own file is kept
+    assert_eq!(
+        extract_covered_lines(&report, &roots),
+        HashMap::from([("src/lib.rs".to_string(), HashSet::from([2]))])
+    );
+}
+
+#[test]
+fn test_collect_from_reports_without_package_files() {
+    // Setup test data - a report none of whose files are in the local workspace
+    let workspace = TempDir::default();
+    let roots = PackageRoots::new(
+        vec![("app".to_string(), workspace.join("app"))],
+        &workspace,
+        &["app".to_string()],
+        &[],
+    )
+    .unwrap();
+    let reports_dir = workspace.join("reports");
+    let path = test_report_path(&reports_dir, "lib:app::tests::test_run");
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    let report = report(vec![source_file(
+        &["/", "ci", "build", "app", "src", "lib.rs"],
+        &[(1, 1)],
+    )]);
+    fs::write(path, serde_json::to_string(&report).unwrap()).unwrap();
+
+    // Execute
+    let result = collect_from_reports(&roots, &reports_dir);
+
+    // Verify
+    assert!(result.is_err());
+}